@@ -0,0 +1,237 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Error, Fields, Ident, LitStr, Result, Type};
+
+/// Derives `JNICompatible` for a struct with named fields, mapping it to a Java record whose
+/// components are the struct's fields, in the same order.
+///
+/// The Java class is given with `#[jni(class = "com/example/Name")]` on the struct. Components are
+/// named after their field in camel case, unless renamed with `#[jni(name = "...")]` on the field.
+/// Fields of a primitive type (`i8`, `i16`, `u16`, `i32`, `i64`, `f32`, `f64`, `bool`) map to the
+/// matching Java primitive, every other field has to be `JNICompatible` itself.
+///
+/// Generated code refers to `crate::binding::jni::JNICompatible` and goes through
+/// `crate::binding::cache` for every class and method, so this is only meant to be used from within
+/// `s4rs`.
+#[proc_macro_derive(JNICompatible, attributes(jni))]
+pub fn derive_jni_compatible(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// A Java primitive a field maps to.
+struct Primitive {
+    /// Type descriptor in a JNI signature.
+    descriptor: &'static str,
+    /// `JValueGen` variant holding it.
+    variant: &'static str,
+    /// `JValueGen` method unwrapping it.
+    getter: &'static str
+}
+
+/// Returns the primitive a Rust type maps to, if it's one of the types that map to one.
+fn primitive(ty: &Type) -> Option<Primitive> {
+    let Type::Path(path) = ty else { return None };
+    if path.qself.is_some() || path.path.segments.len() != 1 { return None }
+
+    let (descriptor, variant, getter) = match path.path.segments[0].ident.to_string().as_str() {
+        "i8" => ("B", "Byte", "b"),
+        "i16" => ("S", "Short", "s"),
+        "u16" => ("C", "Char", "c"),
+        "i32" => ("I", "Int", "i"),
+        "i64" => ("J", "Long", "j"),
+        "f32" => ("F", "Float", "f"),
+        "f64" => ("D", "Double", "d"),
+        "bool" => ("Z", "Bool", "z"),
+        _ => return None
+    };
+    Some(Primitive { descriptor, variant, getter })
+}
+
+/// A field, and how it maps to its record component.
+struct Component<'a> {
+    field: &'a Ident,
+    ty: &'a Type,
+    /// Name of the record component, which is also the name of its accessor.
+    name: String,
+    primitive: Option<Primitive>
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let class = class_name(&input)?;
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(&input.ident, "JNICompatible can only be derived for structs"))
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(&input.ident, "JNICompatible can only be derived for structs with named fields"))
+    };
+
+    let components = fields.named.iter()
+        .map(|field| {
+            let ident = field.ident.as_ref().expect("named fields have names");
+            Ok(Component {
+                field: ident,
+                ty: &field.ty,
+                name: component_name(&field.attrs)?.unwrap_or_else(|| camel_case(&ident.to_string())),
+                primitive: primitive(&field.ty)
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let trait_path: TokenStream2 = quote!(crate::binding::jni::JNICompatible);
+    let cache: TokenStream2 = quote!(crate::binding::cache);
+
+    // every field that isn't a primitive converts itself, which also covers generic parameters
+    let mut generics = input.generics.clone();
+    generics.params.insert(0, parse_quote!('local));
+    let where_clause = generics.make_where_clause();
+    for component in components.iter().filter(|component| component.primitive.is_none()) {
+        let ty = component.ty;
+        where_clause.predicates.push(parse_quote!(#ty: #trait_path<'local>));
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let name = &input.ident;
+
+    let descriptors = components.iter().map(|component| descriptor(component, &trait_path));
+    let converted = components.iter()
+        .filter(|component| component.primitive.is_none())
+        .map(|component| {
+            let field = component.field;
+            let local = local(field);
+            quote!(let #local = #trait_path::to_jni(&self.#field, env)?;)
+        });
+    let args = components.iter().map(|component| {
+        let field = component.field;
+        match &component.primitive {
+            Some(primitive) => {
+                let variant = Ident::new(primitive.variant, Span::call_site());
+                match primitive.descriptor {
+                    "Z" => quote!(::jni::objects::JValueGen::Bool(self.#field as ::jni::sys::jboolean)),
+                    _ => quote!(::jni::objects::JValueGen::#variant(self.#field))
+                }
+            }
+            None => {
+                let local = local(field);
+                quote!(::jni::objects::JValueGen::Object(&#local))
+            }
+        }
+    });
+
+    let reads = components.iter().map(|component| {
+        let local = local(component.field);
+        let accessor = &component.name;
+        let descriptor = descriptor(component, &trait_path);
+        match &component.primitive {
+            Some(primitive) => {
+                let getter = Ident::new(primitive.getter, Span::call_site());
+                quote!(let #local = #cache::call_method(env, &object, <Self as #trait_path<'local>>::CLASS, #accessor, &::std::format!("(){}", #descriptor), &[])?.#getter()?;)
+            }
+            None => {
+                let ty = component.ty;
+                quote! {
+                    let #local = #cache::call_method(env, &object, <Self as #trait_path<'local>>::CLASS, #accessor, &::std::format!("(){}", #descriptor), &[])?.l()?;
+                    let #local = <#ty as #trait_path<'local>>::from_jni(env, #local)?;
+                }
+            }
+        }
+    });
+    let fields = components.iter().map(|component| {
+        let field = component.field;
+        let local = local(field);
+        quote!(#field: #local)
+    });
+
+    Ok(quote! {
+        impl #impl_generics #trait_path<'local> for #name #ty_generics #where_clause {
+            const CLASS: &'static str = #class;
+
+            fn to_jni(&self, env: &mut ::jni::JNIEnv<'local>) -> ::eyre::Result<::jni::objects::JObject<'local>> {
+                #(#converted)*
+                let sig = ::std::format!("({})V", [#(#descriptors),*].concat());
+                #cache::new_object(env, <Self as #trait_path<'local>>::CLASS, &sig, &[#(#args),*])
+            }
+
+            fn from_jni(env: &mut ::jni::JNIEnv<'local>, object: ::jni::objects::JObject<'local>) -> ::eyre::Result<Self>
+            where
+                Self: Sized
+            {
+                #(#reads)*
+                Ok(Self { #(#fields),* })
+            }
+        }
+    })
+}
+
+/// Returns the name of the local holding a field's value, which can't clash with `env` or `object`.
+fn local(field: &Ident) -> Ident {
+    format_ident!("__{}", field)
+}
+
+/// Returns an expression for the type descriptor of a component, as a `String`.
+fn descriptor(component: &Component, trait_path: &TokenStream2) -> TokenStream2 {
+    match &component.primitive {
+        Some(primitive) => {
+            let descriptor = primitive.descriptor;
+            quote!(::std::string::String::from(#descriptor))
+        }
+        None => {
+            let ty = component.ty;
+            quote!(<#ty as #trait_path<'local>>::signature())
+        }
+    }
+}
+
+/// Reads the Java class out of `#[jni(class = "...")]`.
+fn class_name(input: &DeriveInput) -> Result<LitStr> {
+    let mut class = None;
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("jni")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("class") {
+                class = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `class = \"...\"`"))
+            }
+        })?;
+    }
+
+    class.ok_or_else(|| Error::new_spanned(&input.ident, "missing #[jni(class = \"...\")]"))
+}
+
+/// Reads a component name out of `#[jni(name = "...")]`, if the field has one.
+fn component_name(attrs: &[Attribute]) -> Result<Option<String>> {
+    let mut name = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("jni")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("expected `name = \"...\"`"))
+            }
+        })?;
+    }
+
+    Ok(name)
+}
+
+/// Turns a snake case field name into the camel case Java uses.
+fn camel_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = false;
+    for ch in name.trim_start_matches("r#").chars() {
+        if ch == '_' {
+            upper = !out.is_empty();
+        } else if upper {
+            out.extend(ch.to_uppercase());
+            upper = false;
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
@@ -0,0 +1,190 @@
+use dashmap::{DashMap, Equivalent};
+use eyre::{eyre, Result};
+use jni::objects::{GlobalRef, JMethodID, JObject, JStaticMethodID, JValue, JValueOwned};
+use jni::signature::{JavaType, ReturnType, TypeSignature};
+use jni::sys::jvalue;
+use jni::JNIEnv;
+use std::sync::LazyLock;
+
+/// Classes looked up so far, by name.
+static CLASSES: LazyLock<DashMap<String, GlobalRef>> = LazyLock::new(DashMap::new);
+/// Methods and constructors looked up so far.
+static METHODS: LazyLock<DashMap<MemberKey, Method<JMethodID>>> = LazyLock::new(DashMap::new);
+/// Static methods looked up so far.
+static STATIC_METHODS: LazyLock<DashMap<MemberKey, Method<JStaticMethodID>>> = LazyLock::new(DashMap::new);
+/// Enum constants looked up so far. Their keys have an empty signature.
+static CONSTANTS: LazyLock<DashMap<MemberKey, GlobalRef>> = LazyLock::new(DashMap::new);
+
+/// Identifies a member of a class.
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+struct MemberKey {
+    class: String,
+    name: String,
+    sig: String
+}
+
+/// Borrowed `MemberKey`, so that lookups don't have to allocate. Hashes the same way.
+#[derive(Debug, Copy, Clone, Hash)]
+struct MemberRef<'a> {
+    class: &'a str,
+    name: &'a str,
+    sig: &'a str
+}
+
+impl MemberRef<'_> {
+    fn to_key(self) -> MemberKey {
+        MemberKey { class: self.class.to_string(), name: self.name.to_string(), sig: self.sig.to_string() }
+    }
+}
+
+impl Equivalent<MemberKey> for MemberRef<'_> {
+    fn equivalent(&self, key: &MemberKey) -> bool {
+        self.class == key.class && self.name == key.name && self.sig == key.sig
+    }
+}
+
+/// A method looked up by name, along with its parsed signature to check arguments against.
+struct Method<I> {
+    id: I,
+    sig: TypeSignature
+}
+
+/// Returns a global reference to `name`, looking it up the first time it's asked for.
+///
+/// `FindClass` only sees the system class loader on threads attached from native code, which
+/// doesn't know the mod's classes, so these have to be looked up on a Java thread first (see
+/// `preload`).
+pub fn class(env: &mut JNIEnv, name: &str) -> Result<GlobalRef> {
+    if let Some(class) = CLASSES.get(name) { return Ok(class.clone()) }
+
+    let local = env.find_class(name)?;
+    let global = env.new_global_ref(&local)?;
+    env.delete_local_ref(local)?;
+    Ok(CLASSES.entry(name.to_string()).or_insert(global).clone())
+}
+
+/// Looks up `classes` ahead of time. Classes that can't be found are skipped, and looked up on first
+/// use instead.
+pub fn preload(env: &mut JNIEnv, classes: &[&str]) {
+    for name in classes {
+        if class(env, name).is_err() {
+            let _ = env.exception_clear();
+        }
+    }
+}
+
+/// Creates an instance of `class_name` through the constructor with the signature `sig`.
+pub fn new_object<'local>(
+    env: &mut JNIEnv<'local>,
+    class_name: &str,
+    sig: &str,
+    args: &[JValue]
+) -> Result<JObject<'local>> {
+    let class = class(env, class_name)?;
+    let key = MemberRef { class: class_name, name: "<init>", sig };
+    let (id, _) = method(&METHODS, key, args, || Ok(env.get_method_id(&class, key.name, key.sig)?))?;
+    let args: Vec<jvalue> = args.iter().map(JValue::as_jni).collect();
+
+    // SAFETY: the constructor was looked up on this very class, with the signature the arguments
+    // were just checked against
+    Ok(unsafe { env.new_object_unchecked(&class, id, &args)? })
+}
+
+/// Calls the method `name` with the signature `sig` on `object`, which has to be an instance of
+/// `class_name`.
+pub fn call_method<'local>(
+    env: &mut JNIEnv<'local>,
+    object: &JObject,
+    class_name: &str,
+    name: &str,
+    sig: &str,
+    args: &[JValue]
+) -> Result<JValueOwned<'local>> {
+    if object.is_null() { return Err(eyre!("Called {}.{} on null", class_name, name)) }
+    let class = class(env, class_name)?;
+    if !env.is_instance_of(object, &class)? {
+        return Err(eyre!("Called {}.{} on an object of another class", class_name, name))
+    }
+    let key = MemberRef { class: class_name, name, sig };
+    let (id, ret) = method(&METHODS, key, args, || Ok(env.get_method_id(&class, name, sig)?))?;
+    let args: Vec<jvalue> = args.iter().map(JValue::as_jni).collect();
+
+    // SAFETY: `object` is an instance of the class the method was looked up on, and the arguments
+    // were checked against its signature
+    Ok(unsafe { env.call_method_unchecked(object, id, ret, &args)? })
+}
+
+/// Calls the static method `name` with the signature `sig` of `class_name`.
+pub fn call_static_method<'local>(
+    env: &mut JNIEnv<'local>,
+    class_name: &str,
+    name: &str,
+    sig: &str,
+    args: &[JValue]
+) -> Result<JValueOwned<'local>> {
+    let class = class(env, class_name)?;
+    let key = MemberRef { class: class_name, name, sig };
+    let (id, ret) = method(&STATIC_METHODS, key, args, || Ok(env.get_static_method_id(&class, name, sig)?))?;
+    let args: Vec<jvalue> = args.iter().map(JValue::as_jni).collect();
+
+    // SAFETY: the method was looked up on this very class, with the signature the arguments were
+    // just checked against
+    Ok(unsafe { env.call_static_method_unchecked(&class, id, ret, &args)? })
+}
+
+/// Returns the constant `name` of the Java enum `class_name`.
+pub fn enum_constant<'local>(env: &mut JNIEnv<'local>, class_name: &str, name: &str) -> Result<JObject<'local>> {
+    let key = MemberRef { class: class_name, name, sig: "" };
+    if let Some(constant) = CONSTANTS.get(&key).map(|constant| constant.clone()) {
+        return Ok(env.new_local_ref(&constant)?)
+    }
+
+    let class = class(env, class_name)?;
+    let constant = env.get_static_field(&class, name, format!("L{};", class_name))?.l()?;
+    CONSTANTS.insert(key.to_key(), env.new_global_ref(&constant)?);
+    Ok(constant)
+}
+
+/// Returns whether the member `name` of `class_name` has been looked up already. Methods,
+/// constructors (`<init>`) and static methods are identified by their signature `sig`, enum
+/// constants by an empty one.
+pub fn is_cached(class_name: &str, name: &str, sig: &str) -> bool {
+    let key = MemberRef { class: class_name, name, sig };
+    METHODS.contains_key(&key) || STATIC_METHODS.contains_key(&key) || CONSTANTS.contains_key(&key)
+}
+
+/// Returns the ID and return type of a method out of `methods`, looking it up with `lookup` the
+/// first time, after checking `args` against its signature.
+fn method<I: Copy>(
+    methods: &DashMap<MemberKey, Method<I>>,
+    key: MemberRef,
+    args: &[JValue],
+    lookup: impl FnOnce() -> Result<I>
+) -> Result<(I, ReturnType)> {
+    if let Some(method) = methods.get(&key) {
+        check_args(&method.sig, key, args)?;
+        return Ok((method.id, method.sig.ret.clone()))
+    }
+
+    let sig = TypeSignature::from_str(key.sig)?;
+    check_args(&sig, key, args)?;
+    let id = lookup()?;
+    let ret = sig.ret.clone();
+    methods.insert(key.to_key(), Method { id, sig });
+    Ok((id, ret))
+}
+
+/// Checks that there are as many arguments as `sig` takes, and that each is of the right kind.
+/// Calling a method by its ID skips these checks on the Java side, where a mismatch is undefined
+/// behavior rather than an error.
+fn check_args(sig: &TypeSignature, key: MemberRef, args: &[JValue]) -> Result<()> {
+    let matches = sig.args.len() == args.len() && sig.args.iter().zip(args)
+        .all(|(expected, actual)| match expected {
+            JavaType::Primitive(primitive) => actual.primitive_type() == Some(*primitive),
+            _ => actual.primitive_type().is_none()
+        });
+
+    if matches { Ok(()) } else {
+        Err(eyre!("Arguments don't match {}.{}{}", key.class, key.name, key.sig))
+    }
+}
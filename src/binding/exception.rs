@@ -0,0 +1,64 @@
+use crate::binding::cache;
+use eyre::{eyre, Report, Result};
+use jni::JNIEnv;
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Java exception thrown for anything that goes wrong on the native side.
+pub const NATIVE_EXCEPTION: &str = "com/genericbadname/s4mc/NativeException";
+/// Thrown instead if `NATIVE_EXCEPTION` can't be, e.g. when the class isn't on the classpath.
+const FALLBACK_EXCEPTION: &str = "java/lang/RuntimeException";
+
+/// Runs the body of a native entry point. Errors and panics never make it across the FFI boundary:
+/// both are thrown as a `NativeException` instead, and `fallback` is returned to Java, which
+/// ignores it as there's an exception pending.
+///
+/// Entry points run on threads Java knows about, so records logged elsewhere in the meantime are
+/// forwarded first.
+pub fn guard<'local, T>(
+    env: &mut JNIEnv<'local>,
+    fallback: T,
+    body: impl FnOnce(&mut JNIEnv<'local>) -> Result<T>
+) -> T {
+    log::logger().flush();
+    let error = match panic::catch_unwind(AssertUnwindSafe(|| body(env))) {
+        Ok(Ok(value)) => return value,
+        Ok(Err(error)) => error,
+        Err(payload) => eyre!("Native code panicked: {}", panic_message(payload.as_ref()))
+    };
+
+    throw(env, &error);
+    fallback
+}
+
+/// Throws `error` as a `NativeException`, with its whole chain of causes as the message. If a JNI
+/// call failed because of an exception in Java, that one is left pending instead, as it says more
+/// about what went wrong.
+pub fn throw(env: &mut JNIEnv, error: &Report) {
+    if env.exception_check().unwrap_or(false) { return }
+
+    let message = exception_message(error);
+    let thrown = cache::class(env, NATIVE_EXCEPTION)
+        .and_then(|class| Ok(env.throw_new(&class, &message)?));
+    if thrown.is_err() {
+        // looking up the class failed and left its own exception pending
+        let _ = env.exception_clear();
+        let _ = env.throw_new(FALLBACK_EXCEPTION, &message);
+    }
+}
+
+/// Returns the message an error is thrown with: every cause, outermost first.
+pub fn exception_message(error: &Report) -> String {
+    format!("{:#}", error)
+}
+
+/// Returns the message a panic was started with, if it was given one.
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("no message")
+    }
+}
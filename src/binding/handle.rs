@@ -0,0 +1,60 @@
+use dashmap::DashMap;
+use eyre::{eyre, Result};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+/// Native objects handed out to Java as opaque `long` handles. Handles are never reused, so one
+/// that has been freed stays invalid instead of pointing at whatever was created after it, and
+/// `0` is never a valid handle. Lookups hand out a shared reference, so freeing an object while
+/// another thread is still using it only drops it once that thread is done.
+pub struct HandleRegistry<T> {
+    next: AtomicI64,
+    entries: DashMap<i64, Arc<T>>
+}
+
+impl <T> HandleRegistry<T> {
+    pub fn new() -> HandleRegistry<T> {
+        HandleRegistry {
+            next: AtomicI64::new(1),
+            entries: DashMap::new()
+        }
+    }
+
+    /// Takes ownership of `value`, returning its handle.
+    pub fn insert(&self, value: T) -> i64 {
+        let handle = self.next.fetch_add(1, Ordering::Relaxed);
+        self.entries.insert(handle, Arc::new(value));
+        handle
+    }
+
+    /// Returns the object behind `handle`, or an error if it was never handed out or has already
+    /// been freed.
+    pub fn get(&self, handle: i64) -> Result<Arc<T>> {
+        self.entries.get(&handle)
+            .map(|entry| Arc::clone(entry.value()))
+            .ok_or_else(|| eyre!("Invalid or freed handle: {}", handle))
+    }
+
+    /// Frees the object behind `handle`. Freeing the same handle twice is an error rather than
+    /// undefined behavior.
+    pub fn remove(&self, handle: i64) -> Result<()> {
+        self.entries.remove(&handle)
+            .map(|_| ())
+            .ok_or_else(|| eyre!("Tried to free an invalid or already freed handle: {}", handle))
+    }
+
+    /// Returns the amount of live handles.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl <T> Default for HandleRegistry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
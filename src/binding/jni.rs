@@ -1,7 +1,10 @@
+use crate::binding::cache;
 use eyre::Result;
 use jni::objects::{JObject, JValueGen};
 use jni::JNIEnv;
 
+pub use s4rs_derive::JNICompatible;
+
 /// A type which is easily transferable between a Rust representation and a Java `Object`.
 /// For `struct`s, these are usually going to be `Record`s as they are the closest
 /// analogue to a pure-data class.
@@ -11,32 +14,109 @@ pub trait JNICompatible<'local> {
 
     /// Constructs the corresponding `JObject` of this type.
     fn to_jni(&self, env: &mut JNIEnv<'local>) -> Result<JObject<'local>>;
-    /// Constructs this type from a `JObject` that may or may not contain valid values.
+    /// Constructs this type from a `JObject` that may or may not contain valid values. The result must
+    /// not hold on to `object` or any other local reference, as lists convert each element in a local
+    /// frame of its own.
     fn from_jni(env: &mut JNIEnv<'local>, object: JObject<'local>) -> Result<Self> where Self: Sized;
+
+    /// Type descriptor of this type in a JNI signature. Only differs from `CLASS` for types that are
+    /// exposed as an interface, rather than the class they're constructed as.
+    fn signature() -> String where Self: Sized {
+        format!("L{};", Self::CLASS)
+    }
 }
 
+/// What a `Vec` is exposed as, whichever list it was constructed as.
+const LIST_INTERFACE: &str = "java/util/List";
+/// Local references each element of a `Vec` may use while being converted.
+const ELEMENT_FRAME: i32 = 16;
+
 // Represent a Vec as a list, close enough (I know java has vectors, but I don't care)
 impl <'local, T: JNICompatible<'local>> JNICompatible<'local> for Vec<T> {
     const CLASS: &'static str = "java/util/ArrayList";
 
     fn to_jni(&self, env: &mut JNIEnv<'local>) -> Result<JObject<'local>> {
-        let list_class = env.find_class(Self::CLASS)?;
-        let list_obj = env.new_object(list_class, "()V", &[])?;
+        let list_obj = cache::new_object(env, Self::CLASS, "(I)V", &[JValueGen::Int(self.len() as i32)])?;
 
         for object in self.iter() {
-            let val = JValueGen::Object(&object.to_jni(env)?);
-            env.call_method(&list_obj, "add", "<T:Ljava/lang/Object;>", &[
-                val
-            ])?;
+            // SAFETY: `body` only returns `()`, the element is handed to the list before the frame is popped
+            unsafe {
+                in_local_frame(env, |env| {
+                    let val = object.to_jni(env)?;
+                    cache::call_method(env, &list_obj, Self::CLASS, "add", "(Ljava/lang/Object;)Z", &[JValueGen::Object(&val)])?;
+                    Ok(())
+                })?;
+            }
         }
 
         Ok(list_obj)
     }
 
+    fn signature() -> String {
+        format!("L{};", LIST_INTERFACE)
+    }
+
+    /// Reads any `java.util.List`, not just an `ArrayList`.
+    fn from_jni(env: &mut JNIEnv<'local>, object: JObject<'local>) -> Result<Self>
+    where
+        Self: Sized
+    {
+        let len = cache::call_method(env, &object, LIST_INTERFACE, "size", "()I", &[])?.i()?;
+
+        (0..len)
+            // SAFETY: `from_jni` reads the element into a Rust value, it keeps no reference to it
+            .map(|idx| unsafe {
+                in_local_frame(env, |env| {
+                    let element = cache::call_method(env, &object, LIST_INTERFACE, "get", "(I)Ljava/lang/Object;", &[JValueGen::Int(idx)])?.l()?;
+                    T::from_jni(env, element)
+                })
+            })
+            .collect()
+    }
+}
+
+/// An `Option` is `null` when empty, so that optional record components don't need a class of their
+/// own.
+impl <'local, T: JNICompatible<'local>> JNICompatible<'local> for Option<T> {
+    const CLASS: &'static str = T::CLASS;
+
+    fn to_jni(&self, env: &mut JNIEnv<'local>) -> Result<JObject<'local>> {
+        match self {
+            Some(value) => value.to_jni(env),
+            None => Ok(JObject::null())
+        }
+    }
+
     fn from_jni(env: &mut JNIEnv<'local>, object: JObject<'local>) -> Result<Self>
     where
         Self: Sized
     {
-        todo!()
+        if object.is_null() { return Ok(None) }
+        Ok(Some(T::from_jni(env, object)?))
+    }
+
+    fn signature() -> String {
+        T::signature()
     }
-}
\ No newline at end of file
+}
+
+/// Runs `body` in a local reference frame of its own, so that a long list doesn't pile up references
+/// to every element (and everything in it) until control returns to Java. Unlike
+/// `JNIEnv::with_local_frame`, `body` keeps the caller's lifetime, which is what `JNICompatible`
+/// needs.
+///
+/// # Safety
+///
+/// Every local reference `body` creates is deleted when it returns, while still typed as `'local`.
+/// `body` must not return, nor stash anywhere, a reference it created (or anything holding one), or
+/// it will be used after it is deleted.
+unsafe fn in_local_frame<'local, T>(
+    env: &mut JNIEnv<'local>,
+    body: impl FnOnce(&mut JNIEnv<'local>) -> Result<T>
+) -> Result<T> {
+    env.push_local_frame(ELEMENT_FRAME)?;
+    let out = body(env);
+    // SAFETY: the caller guarantees nothing that refers into the frame comes out of `body`
+    unsafe { env.pop_local_frame(&JObject::null())?; }
+    out
+}
@@ -0,0 +1,51 @@
+use crate::binding::cache;
+use crate::binding::exception::NATIVE_EXCEPTION;
+use crate::binding::logger::{self, LOG_CLASS};
+use crate::binding::jni::JNICompatible;
+use crate::pathing::action::{Interaction, MoveKind, SpatialAction, INTERACTION_KIND_CLASS};
+use crate::pathing::agent::{Agent, Hitbox, Tool};
+use crate::pathing::block::ToolKind;
+use crate::pathing::data::{PathNode, PathResult, PathStatus};
+use crate::pathing::math::{Face, Vector3i};
+use jni::sys::{jint, JNI_VERSION_1_8};
+use jni::JavaVM;
+use std::ffi::c_void;
+
+/// Every class conversions and entry points use. These are looked up while loading, as that's the
+/// one time native code is sure to be on a thread whose class loader knows the mod's classes.
+pub fn java_classes() -> [&'static str; 18] {
+    [
+        Vector3i::CLASS,
+        Face::CLASS,
+        MoveKind::CLASS,
+        SpatialAction::<Vector3i>::CLASS,
+        INTERACTION_KIND_CLASS,
+        Interaction::<Vector3i>::CLASS,
+        PathNode::<Vector3i>::CLASS,
+        PathStatus::CLASS,
+        PathResult::<Vector3i>::CLASS,
+        Agent::CLASS,
+        Hitbox::CLASS,
+        Tool::CLASS,
+        ToolKind::CLASS,
+        Vec::<Vector3i>::CLASS,
+        "java/util/List",
+        "java/lang/Enum",
+        NATIVE_EXCEPTION,
+        LOG_CLASS
+    ]
+}
+
+/// Called by the JVM when the library is loaded. Logs are forwarded to Java from here on.
+#[unsafe(no_mangle)]
+#[allow(non_snake_case)]
+pub extern "system" fn JNI_OnLoad(vm: JavaVM, _reserved: *mut c_void) -> jint {
+    if let Ok(mut env) = vm.get_env() {
+        cache::preload(&mut env, &java_classes());
+    }
+    // only fails if the library was loaded twice into the same process, where the first logger
+    // keeps working
+    let _ = logger::install(vm);
+
+    JNI_VERSION_1_8
+}
@@ -0,0 +1,178 @@
+use crate::binding::cache;
+use crate::binding::exception::guard;
+use eyre::{eyre, Result};
+use jni::objects::{JClass, JValueGen};
+use jni::sys::jint;
+use jni::{JNIEnv, JavaVM};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::cell::Cell;
+use std::{iter, mem};
+use std::sync::{Mutex, OnceLock};
+
+/// Java class records are forwarded to, through its static `log(int level, String target, String
+/// message)`. Levels are numbered like `log::Level`, from `1` for errors to `5` for traces.
+pub const LOG_CLASS: &str = "com/genericbadname/s4mc/NativeLog";
+const LOG_SIG: &str = "(ILjava/lang/String;Ljava/lang/String;)V";
+
+/// Most verbose level logged until Java asks for something else.
+pub const DEFAULT_MAX_LEVEL: LevelFilter = LevelFilter::Info;
+
+/// Most records kept around for a thread that can call into Java. Anything past that is dropped.
+const MAX_PENDING: usize = 1024;
+
+static LOGGER: OnceLock<JavaLogger> = OnceLock::new();
+
+/// Forwards log records to the Java side, where they end up in the game's log.
+pub struct JavaLogger {
+    vm: JavaVM,
+    /// Records waiting to be forwarded, oldest first, along with how many were dropped.
+    pending: Mutex<(Vec<PendingRecord>, usize)>,
+    /// Held while forwarding, so that records reach Java in the order they were logged.
+    forwarding: Mutex<()>
+}
+
+/// A record kept until it can be forwarded.
+struct PendingRecord {
+    level: Level,
+    target: String,
+    message: String
+}
+
+thread_local! {
+    /// Whether this thread is forwarding records already, in which case anything Java logs back
+    /// into native code is left for it to pick up.
+    static FORWARDING: Cell<bool> = const { Cell::new(false) };
+}
+
+impl Log for JavaLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) { return }
+
+        // attaching a thread just to log would leave it attached for good, so records from threads
+        // Java doesn't know about wait for one that it does
+        self.defer(PendingRecord {
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string()
+        });
+        self.flush();
+    }
+
+    /// Forwards whatever is pending, if this thread can call into Java.
+    fn flush(&self) {
+        if let Ok(mut env) = self.vm.get_env() {
+            self.forward_pending(&mut env);
+        }
+    }
+}
+
+impl JavaLogger {
+    /// Keeps a record to forward later, unless too many are pending already.
+    fn defer(&self, record: PendingRecord) {
+        let Ok(mut pending) = self.pending.lock() else { return };
+        let (records, dropped) = &mut *pending;
+        if records.len() < MAX_PENDING {
+            records.push(record);
+        } else {
+            *dropped += 1;
+        }
+    }
+
+    /// Forwards pending records in the order they were logged, until none are left or one fails.
+    fn forward_pending(&self, env: &mut JNIEnv) {
+        if FORWARDING.get() { return }
+        let Ok(_forwarding) = self.forwarding.lock() else { return };
+        FORWARDING.set(true);
+
+        loop {
+            // records logged meanwhile are picked up on the next round
+            let Ok((records, dropped)) = self.pending.lock().map(|mut pending| mem::take(&mut *pending)) else { break };
+            if records.is_empty() && dropped == 0 { break }
+
+            let notice = (dropped > 0).then(|| PendingRecord {
+                level: Level::Warn,
+                target: module_path!().to_string(),
+                message: format!("{} records were dropped while waiting to be logged", dropped)
+            });
+            let mut records = notice.into_iter().chain(records);
+            if let Some(failed) = records.find(|record| forward(env, record).is_err()) {
+                self.requeue(iter::once(failed).chain(records).collect());
+                break
+            }
+        }
+
+        FORWARDING.set(false);
+    }
+
+    /// Puts records that couldn't be forwarded back in front of any logged since.
+    fn requeue(&self, mut records: Vec<PendingRecord>) {
+        let Ok(mut pending) = self.pending.lock() else { return };
+        let (newer, dropped) = mem::take(&mut *pending);
+        records.extend(newer);
+        let over = records.len().saturating_sub(MAX_PENDING);
+        records.truncate(MAX_PENDING);
+        *pending = (records, dropped + over);
+    }
+}
+
+/// Hands a record to `LOG_CLASS` on a thread that is attached to the JVM.
+fn forward(env: &mut JNIEnv, record: &PendingRecord) -> Result<()> {
+    // Java can't be called into with an exception pending, and clearing it would swallow it
+    if env.exception_check()? { return Err(eyre!("An exception is pending")) }
+
+    let forwarded = env.with_local_frame(4, |env| -> Result<()> {
+        let target = env.new_string(&record.target)?;
+        let message = env.new_string(&record.message)?;
+        cache::call_static_method(env, LOG_CLASS, "log", LOG_SIG, &[
+            JValueGen::Int(record.level as jint),
+            JValueGen::Object(&target),
+            JValueGen::Object(&message)
+        ])?;
+        Ok(())
+    });
+
+    // whatever the logger threw is of no concern to the code that logged
+    if forwarded.is_err() && env.exception_check().unwrap_or(false) {
+        let _ = env.exception_clear();
+    }
+    forwarded
+}
+
+/// Installs the logger for the whole library, at `DEFAULT_MAX_LEVEL`. Fails if a logger is already
+/// installed.
+pub fn install(vm: JavaVM) -> Result<()> {
+    let logger = LOGGER.get_or_init(|| JavaLogger { vm, pending: Mutex::new((Vec::new(), 0)), forwarding: Mutex::new(()) });
+    log::set_logger(logger).map_err(|_| eyre!("A logger is already installed"))?;
+    log::set_max_level(DEFAULT_MAX_LEVEL);
+    Ok(())
+}
+
+/// Converts a level coming from Java: `0` turns logging off, `1` to `5` go from errors to traces.
+pub fn level_filter(level: jint) -> Result<LevelFilter> {
+    match level {
+        0 => Ok(LevelFilter::Off),
+        1 => Ok(Level::Error.to_level_filter()),
+        2 => Ok(Level::Warn.to_level_filter()),
+        3 => Ok(Level::Info.to_level_filter()),
+        4 => Ok(Level::Debug.to_level_filter()),
+        5 => Ok(Level::Trace.to_level_filter()),
+        _ => Err(eyre!("Unknown log level {}", level))
+    }
+}
+
+/// Sets the most verbose level that gets forwarded, numbered like in `level_filter`.
+#[unsafe(no_mangle)]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_com_genericbadname_s4mc_NativeLog_setMaxLevel
+    <'local>(
+    mut env: JNIEnv<'local>, _class: JClass<'local>,
+    level: jint) {
+    guard(&mut env, (), |_env| {
+        log::set_max_level(level_filter(level)?);
+        Ok(())
+    })
+}
@@ -1,3 +1,9 @@
-mod pathing;
-mod util;
-pub mod jni;
\ No newline at end of file
+pub mod pathing;
+pub mod world;
+pub mod load;
+pub mod util;
+pub mod jni;
+pub mod handle;
+pub mod exception;
+pub mod cache;
+pub mod logger;
\ No newline at end of file
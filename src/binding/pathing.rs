@@ -1,46 +1,198 @@
+use crate::binding::cache;
+use crate::binding::exception::guard;
+use crate::binding::handle::HandleRegistry;
 use crate::binding::jni::JNICompatible;
-use crate::config::Configuration;
+use crate::binding::world::WORLDS;
 use crate::pathing::action::default_moveset;
+use crate::pathing::agent::Agent;
 use crate::pathing::algorithm::PathCalculator;
-use crate::pathing::data::PathNode;
+use crate::pathing::data::PathResult;
+use crate::pathing::goal::GoalExact;
 use crate::pathing::math::Vector3i;
+use crate::pathing::pool::{Callback, PathPool, RequestStatus};
 use crate::pathing::world::VoxelSpace;
-use eyre::Result;
-use jni::objects::{JClass, JObject};
-use jni::sys::jobject;
-use jni::JNIEnv;
+use eyre::{eyre, Result};
+use jni::objects::{GlobalRef, JClass, JObject, JValueGen};
+use jni::sys::{jboolean, jint, jlong, jobject, JNI_FALSE};
+use jni::{JNIEnv, JavaVM};
+use log::error;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::thread;
 
+/// A calculator kept alive between JNI calls. It holds on to its world, so freeing the world only
+/// drops it once every calculator using it is freed too.
+type NativeCalculator = Mutex<PathCalculator<Vector3i, Arc<VoxelSpace>>>;
+
+/// Calculators created from Java, keyed by their handle.
+static CALCULATORS: LazyLock<HandleRegistry<NativeCalculator>> = LazyLock::new(HandleRegistry::new);
+/// Workers for requests made with `submit`. Half the cores are left to the game.
+static POOL: LazyLock<PathPool<Vector3i, Arc<VoxelSpace>>> = LazyLock::new(|| {
+    let threads = thread::available_parallelism().map_or(1, |cores| cores.get() / 2);
+    PathPool::new(threads)
+});
+
+/// Interface of the callbacks given to `submit`.
+const CONSUMER_CLASS: &str = "java/util/function/Consumer";
+
+/// Values returned by `PathRequest.poll`.
+const REQUEST_UNKNOWN: jint = 0;
+const REQUEST_PENDING: jint = 1;
+const REQUEST_DONE: jint = 2;
+
+/// Creates a calculator on the world behind `world`, returning its handle. The calculator uses the
+/// world's configuration, so that what the world allows and what the calculator expects can't
+/// disagree.
+#[unsafe(no_mangle)]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_com_genericbadname_s4mc_pathing_PathCalculator_create
+    <'local>(
+    mut env: JNIEnv<'local>, _class: JClass<'local>,
+    world: jlong) -> jlong {
+    guard(&mut env, 0, |_env| {
+        let space = WORLDS.get(world)?;
+        let calc = PathCalculator::new(default_moveset(), *space.config(), Box::new(space));
+
+        Ok(CALCULATORS.insert(Mutex::new(calc)))
+    })
+}
+
+/// Calculates a path for `agent`, which may be `null` for an empty-handed player.
 #[unsafe(no_mangle)]
 #[allow(non_snake_case)]
 pub extern "system" fn Java_com_genericbadname_s4mc_pathing_PathCalculator_calculate
     <'local>(
     mut env: JNIEnv<'local>, _class: JClass<'local>,
+    handle: jlong,
     start: JObject<'local>,
-    end: JObject<'local>) -> jobject {
-    let try_this: Result<JObject<'local>> = (move || {
-        let config = Configuration::new();
-        let moves = default_moveset();
-        let space = Box::new(VoxelSpace::new());
-
-        let mut calc = PathCalculator::new(moves, config, space);
-        let start_vec = Vector3i::from_jni(&mut env, start)?;
-        let end_vec = Vector3i::from_jni(&mut env, end)?;
-
-        let out = match calc.calculate(start_vec, end_vec) {
-            Err(_) => Vec::<PathNode<Vector3i>>::new().to_jni(&mut env)?,
-            Ok(path) => {
-                path.to_jni(&mut env)?
-            }
+    end: JObject<'local>,
+    agent: JObject<'local>) -> jobject {
+    guard(&mut env, JObject::null().into_raw(), |env| {
+        let calc = CALCULATORS.get(handle)?;
+        let mut calc = calc.lock()
+            .map_err(|_| eyre!("Calculator {} was poisoned by an earlier panic", handle))?;
+        let start_vec = Vector3i::from_jni(env, start)?;
+        let end_vec = Vector3i::from_jni(env, end)?;
+        let agent = Option::<Agent>::from_jni(env, agent)?.unwrap_or_default();
+
+        calc.set_agent(agent);
+        let result = calc.calculate(start_vec, end_vec)?;
+
+        Ok(result.to_jni(env)?.into_raw())
+    })
+}
+
+/// Queues a calculation for `agent` (or an empty-handed player, if `null`) on a worker thread,
+/// returning its request ID right away. If `callback` (a
+/// `java.util.function.Consumer<PathResult>`) is given, it's called on the worker thread once the
+/// calculation is done. Otherwise, the request has to be polled and its result taken with
+/// `PathRequest`.
+#[unsafe(no_mangle)]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_com_genericbadname_s4mc_pathing_PathCalculator_submit
+    <'local>(
+    mut env: JNIEnv<'local>, _class: JClass<'local>,
+    handle: jlong,
+    start: JObject<'local>,
+    end: JObject<'local>,
+    agent: JObject<'local>,
+    callback: JObject<'local>) -> jlong {
+    guard(&mut env, 0, |env| {
+        let calc = CALCULATORS.get(handle)?;
+        let start_vec = Vector3i::from_jni(env, start)?;
+        let end_vec = Vector3i::from_jni(env, end)?;
+        let agent = Option::<Agent>::from_jni(env, agent)?.unwrap_or_default();
+
+        let callback = if callback.is_null() {
+            None
+        } else {
+            let vm = env.get_java_vm()?;
+            let callback = env.new_global_ref(callback)?;
+            Some(Box::new(move |id, result| {
+                if let Err(e) = deliver(&vm, &callback, result) {
+                    error!("Failed to deliver the result of path request {}: {:#}", id, e);
+                }
+            }) as Callback<Vector3i>)
         };
 
-        Ok(out)
-    })();
+        Ok(POOL.submit(calc, start_vec, Box::new(GoalExact(end_vec)), agent, callback)? as jlong)
+    })
+}
+
+/// Hands a finished request's result to its callback. Runs on a worker thread, which is attached
+/// to the JVM the first time and stays attached.
+fn deliver(vm: &JavaVM, callback: &GlobalRef, result: Result<PathResult<Vector3i>>) -> Result<()> {
+    let result = result.unwrap_or_else(|e| {
+        error!("Path calculation failed: {:#}", e);
+        PathResult::failed()
+    });
+
+    let mut env = vm.attach_current_thread_permanently()?;
+    // the thread never returns to Java, so local references have to be cleaned up by hand
+    let delivered = env.with_local_frame(16, |env| -> Result<()> {
+        let result = result.to_jni(env)?;
+        cache::call_method(env, callback.as_obj(), CONSUMER_CLASS, "accept", "(Ljava/lang/Object;)V", &[
+            JValueGen::Object(&result)
+        ])?;
+        Ok(())
+    });
 
-    match try_this {
-        Ok(obj) => *obj,
-        Err(e) => {
-            eprintln!("Error while unwrapping path: {:?}", e);
-            *JObject::null()
-        }
+    // nobody is there to catch what the callback throws
+    if env.exception_check()? {
+        env.exception_describe()?;
+        env.exception_clear()?;
     }
-}
\ No newline at end of file
+    delivered
+}
+
+/// Returns where a request is at: `0` if there is no such request (anymore), `1` if it's still
+/// running and `2` if its result is ready to be taken. Requests with a callback are forgotten once
+/// the callback has been called.
+#[unsafe(no_mangle)]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_com_genericbadname_s4mc_pathing_PathRequest_poll
+    <'local>(
+    mut env: JNIEnv<'local>, _class: JClass<'local>,
+    id: jlong) -> jint {
+    guard(&mut env, REQUEST_UNKNOWN, |_env| {
+        Ok(match POOL.poll(id as u64) {
+            None => REQUEST_UNKNOWN,
+            Some(RequestStatus::Pending) => REQUEST_PENDING,
+            Some(RequestStatus::Done) => REQUEST_DONE
+        })
+    })
+}
+
+/// Returns the result of a finished request and forgets about it. Throws if the request isn't
+/// done, or if the calculation failed.
+#[unsafe(no_mangle)]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_com_genericbadname_s4mc_pathing_PathRequest_take
+    <'local>(
+    mut env: JNIEnv<'local>, _class: JClass<'local>,
+    id: jlong) -> jobject {
+    guard(&mut env, JObject::null().into_raw(), |env| {
+        let result = POOL.take(id as u64)
+            .ok_or_else(|| eyre!("Path request {} is unknown or still running", id))??;
+        Ok(result.to_jni(env)?.into_raw())
+    })
+}
+
+/// Asks a request to stop early. Returns whether there was such a request.
+#[unsafe(no_mangle)]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_com_genericbadname_s4mc_pathing_PathRequest_cancel
+    <'local>(
+    mut env: JNIEnv<'local>, _class: JClass<'local>,
+    id: jlong) -> jboolean {
+    guard(&mut env, JNI_FALSE, |_env| Ok(POOL.cancel(id as u64).into()))
+}
+
+/// Frees a calculator. Freeing one that was already freed throws.
+#[unsafe(no_mangle)]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_com_genericbadname_s4mc_pathing_PathCalculator_free
+    <'local>(
+    mut env: JNIEnv<'local>, _class: JClass<'local>,
+    handle: jlong) {
+    guard(&mut env, (), |_env| CALCULATORS.remove(handle))
+}
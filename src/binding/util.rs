@@ -1,19 +1,50 @@
-use eyre::Result;
+use crate::binding::cache;
+use crate::pathing::chunk::{BlockState, SECTION_VOLUME};
+use eyre::{eyre, Result};
 use jni::objects::{JObject, JValueGen};
 use jni::JNIEnv;
 
-const LIST_CLASS: &'static str = "java/util/ArrayList";
-const LIST_CTOR: &'static str = "()V";
+const LIST_CLASS: &str = "java/util/ArrayList";
+const LIST_CTOR: &str = "()V";
+const LIST_ADD: &str = "(Ljava/lang/Object;)Z";
+const ENUM_CLASS: &str = "java/lang/Enum";
 
 pub fn new_list<'local>(env: &mut JNIEnv<'local>, objs: Vec<JObject<'local>>) -> Result<JObject<'local>> {
-    let list_class = env.find_class(LIST_CLASS)?;
-    let list_obj = env.new_object(list_class, LIST_CTOR, &[])?;
+    let list_obj = cache::new_object(env, LIST_CLASS, LIST_CTOR, &[])?;
 
     for object in objs.iter() {
-        env.call_method(&list_obj, "add", "<T:Ljava/lang/Object;>", &[
+        cache::call_method(env, &list_obj, LIST_CLASS, "add", LIST_ADD, &[
             JValueGen::Object(object)
         ])?;
     }
 
     Ok(list_obj)
-}
\ No newline at end of file
+}
+
+/// Reads a section's worth of palette indices out of raw bytes: either one byte per index, or two
+/// per index in big-endian order (the default for Java's `ByteBuffer`), told apart by length.
+pub fn palette_indices(bytes: &[u8]) -> Result<Vec<u16>> {
+    match bytes.len() {
+        len if len == SECTION_VOLUME => Ok(bytes.iter().map(|idx| *idx as u16).collect()),
+        len if len == SECTION_VOLUME * 2 => Ok(bytes.chunks_exact(2)
+            .map(|idx| u16::from_be_bytes([idx[0], idx[1]]))
+            .collect()),
+        len => Err(eyre!("Expected {} or {} bytes of palette indices, got {}", SECTION_VOLUME, SECTION_VOLUME * 2, len))
+    }
+}
+
+/// Converts a block state ID coming from Java, which only has signed integers, into a `BlockState`.
+pub fn block_state(value: i32) -> Result<BlockState> {
+    BlockState::try_from(value).map_err(|_| eyre!("Block state {} is out of range", value))
+}
+
+/// Returns the constant called `name` of the Java enum `class`.
+pub fn enum_constant<'local>(env: &mut JNIEnv<'local>, class: &str, name: &str) -> Result<JObject<'local>> {
+    cache::enum_constant(env, class, name)
+}
+
+/// Returns the name of a Java enum constant.
+pub fn enum_name(env: &mut JNIEnv, constant: &JObject) -> Result<String> {
+    let name = cache::call_method(env, constant, ENUM_CLASS, "name", "()Ljava/lang/String;", &[])?.l()?;
+    Ok(env.get_string(&name.into())?.into())
+}
@@ -0,0 +1,158 @@
+use crate::binding::cache;
+use crate::binding::exception::guard;
+use crate::binding::handle::HandleRegistry;
+use crate::binding::util::{block_state, palette_indices};
+use crate::config::Configuration;
+use crate::pathing::block::BlockRegistry;
+use crate::pathing::chunk::{section_pos_of, BlockState, ChunkSection};
+use crate::pathing::math::Vector3i;
+use crate::pathing::world::VoxelSpace;
+use crate::vec3i;
+use eyre::{eyre, Result};
+use jni::objects::{JByteBuffer, JClass, JIntArray, JString};
+use jni::sys::{jboolean, jint, jlong, JNI_FALSE, JNI_TRUE};
+use jni::JNIEnv;
+use std::sync::{Arc, LazyLock};
+
+/// Class declaring the position and limit of the buffers given to `loadSection`.
+const BUFFER_CLASS: &str = "java/nio/Buffer";
+
+/// Worlds created from Java, keyed by their handle.
+pub(crate) static WORLDS: LazyLock<HandleRegistry<VoxelSpace>> = LazyLock::new(HandleRegistry::new);
+
+/// Creates an empty world with the block table in `blocks` (see `BlockRegistry::from_json`) and
+/// the configuration in `config` (see `Configuration::from_json`), returning its handle. A `null`
+/// config is the default one, which neither breaks nor places blocks. Calculators created on the
+/// world use its configuration too.
+#[unsafe(no_mangle)]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_com_genericbadname_s4mc_world_NativeWorld_create
+    <'local>(
+    mut env: JNIEnv<'local>, _class: JClass<'local>,
+    blocks: JString<'local>,
+    config: JString<'local>) -> jlong {
+    guard(&mut env, 0, |env| {
+        let blocks: String = env.get_string(&blocks)?.into();
+        let registry = BlockRegistry::from_json(&blocks)?;
+        let config = if config.is_null() {
+            Configuration::new()
+        } else {
+            let config: String = env.get_string(&config)?.into();
+            Configuration::from_json(&config)?
+        };
+        let space = VoxelSpace::new(config, Arc::new(registry));
+
+        Ok(WORLDS.insert(space))
+    })
+}
+
+/// Frees a world. Freeing one that was already freed throws.
+#[unsafe(no_mangle)]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_com_genericbadname_s4mc_world_NativeWorld_free
+    <'local>(
+    mut env: JNIEnv<'local>, _class: JClass<'local>,
+    handle: jlong) {
+    guard(&mut env, (), |_env| WORLDS.remove(handle))
+}
+
+/// Loads a whole chunk section, replacing whatever was there. `indices` is a direct buffer of all
+/// 4096 palette indices in YZX order, one byte or one big-endian `short` each (see
+/// `util::palette_indices`). They're read from position `0` up to the buffer's limit, so a buffer
+/// that was just filled has to be flipped first; one at any other position throws.
+#[unsafe(no_mangle)]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_com_genericbadname_s4mc_world_NativeWorld_loadSection
+    <'local>(
+    mut env: JNIEnv<'local>, _class: JClass<'local>,
+    handle: jlong,
+    x: jint, y: jint, z: jint,
+    palette: JIntArray<'local>,
+    indices: JByteBuffer<'local>) {
+    guard(&mut env, (), |env| {
+        let space = WORLDS.get(handle)?;
+        let palette = read_palette(env, &palette)?;
+        let position = cache::call_method(env, &indices, BUFFER_CLASS, "position", "()I", &[])?.i()?;
+        if position != 0 {
+            return Err(eyre!("Index buffer has to be at position 0, not {}", position))
+        }
+        let limit = cache::call_method(env, &indices, BUFFER_CLASS, "limit", "()I", &[])?.i()?;
+        let address = env.get_direct_buffer_address(&indices)?;
+        // SAFETY: the buffer is direct, so its memory doesn't move, and the caller holds on to it
+        // for the length of this call. Its limit never exceeds its capacity
+        let bytes = unsafe { std::slice::from_raw_parts(address, limit as usize) };
+
+        let section = ChunkSection::from_palette(&palette, &palette_indices(bytes)?)?;
+        space.load_section(vec3i!(x, y, z), section);
+        Ok(())
+    })
+}
+
+/// Like `loadSection`, but with palette indices in an `int[]`, for when a direct buffer isn't at
+/// hand.
+#[unsafe(no_mangle)]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_com_genericbadname_s4mc_world_NativeWorld_loadSectionInts
+    <'local>(
+    mut env: JNIEnv<'local>, _class: JClass<'local>,
+    handle: jlong,
+    x: jint, y: jint, z: jint,
+    palette: JIntArray<'local>,
+    indices: JIntArray<'local>) {
+    guard(&mut env, (), |env| {
+        let space = WORLDS.get(handle)?;
+        let palette = read_palette(env, &palette)?;
+        let indices = read_ints(env, &indices)?.into_iter()
+            .map(|idx| u16::try_from(idx).map_err(|_| eyre!("Palette index {} is out of range", idx)))
+            .collect::<Result<Vec<u16>>>()?;
+
+        let section = ChunkSection::from_palette(&palette, &indices)?;
+        space.load_section(vec3i!(x, y, z), section);
+        Ok(())
+    })
+}
+
+/// Mirrors a single block update. Returns whether the block was set, which it isn't if its section
+/// isn't loaded.
+#[unsafe(no_mangle)]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_com_genericbadname_s4mc_world_NativeWorld_setBlock
+    <'local>(
+    mut env: JNIEnv<'local>, _class: JClass<'local>,
+    handle: jlong,
+    x: jint, y: jint, z: jint,
+    state: jint) -> jboolean {
+    guard(&mut env, JNI_FALSE, |_env| {
+        let space = WORLDS.get(handle)?;
+        let pos = vec3i!(x, y, z);
+        // updates to parts of the world that aren't mirrored are expected, and simply ignored
+        if !space.is_loaded(section_pos_of(pos)) { return Ok(JNI_FALSE) }
+        space.set_block(pos, block_state(state)?)?;
+        Ok(JNI_TRUE)
+    })
+}
+
+/// Unloads every section of the chunk column at chunk coordinates `x`, `z`. Returns the amount of
+/// sections unloaded.
+#[unsafe(no_mangle)]
+#[allow(non_snake_case)]
+pub extern "system" fn Java_com_genericbadname_s4mc_world_NativeWorld_unloadChunk
+    <'local>(
+    mut env: JNIEnv<'local>, _class: JClass<'local>,
+    handle: jlong,
+    x: jint, z: jint) -> jint {
+    guard(&mut env, 0, |_env| Ok(WORLDS.get(handle)?.unload_chunk(x, z) as jint))
+}
+
+/// Reads a palette of block states out of an `int[]`.
+fn read_palette(env: &mut JNIEnv, palette: &JIntArray) -> Result<Vec<BlockState>> {
+    read_ints(env, palette)?.into_iter().map(block_state).collect()
+}
+
+/// Copies a whole `int[]` out in one call.
+fn read_ints(env: &mut JNIEnv, array: &JIntArray) -> Result<Vec<i32>> {
+    let len = env.get_array_length(array)?;
+    let mut out = vec![0; len as usize];
+    env.get_int_array_region(array, 0, &mut out)?;
+    Ok(out)
+}
@@ -1,3 +1,4 @@
+use crate::pathing::block::Climb;
 use crate::pathing::data::HazardMultiplier;
 use eyre::{eyre, Result};
 use log::info;
@@ -8,8 +9,10 @@ use std::io::{BufReader, BufWriter, Read, Write};
 use std::path::Path;
 use std::time::Duration;
 
-/// Configuration for the pathfinding system.
+/// Configuration for the pathfinding system. Missing fields are read as their defaults, so
+/// configuration files written by older versions keep working.
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
 pub struct Configuration {
     /// Hazard multipliers.
     pub hazard: HazardMultiplier,
@@ -18,7 +21,34 @@ pub struct Configuration {
     pub cost_inf: f64,
     /// Maximum allowed pathfinding time. After that, operations will return
     /// with failure states.
-    pub timeout: Duration
+    pub timeout: Duration,
+    /// Health of the pathing entity. Moves that would deal this much damage or more are never
+    /// taken.
+    pub max_health: f64,
+    /// Cost added for every point of damage a move deals.
+    pub damage_cost: f64,
+    /// When diagonal moves may cut past blocked corners.
+    pub corner_cutting: CornerCutting,
+    /// Whether to sprint-jump across gaps.
+    pub allow_parkour: bool,
+    /// Cost added on top of every parkour jump. A missed jump is far worse than a slightly longer
+    /// walk, so this should be enough to only jump when it saves real distance.
+    pub parkour_cost: f64,
+    /// Whether to break blocks that are in the way. Off by default, as breaking blocks in the world
+    /// isn't something to do without being asked.
+    pub allow_breaking: bool,
+    /// Cost added for every block broken, on top of the time it takes to mine.
+    pub break_cost: f64,
+    /// Whether to place throwaway blocks to bridge gaps and pillar up. Off by default, like breaking.
+    pub allow_placing: bool,
+    /// Cost added for every block placed.
+    pub place_cost: f64,
+    /// Cost of climbing a block on each kind of climbable.
+    pub climb_cost: ClimbCost,
+    /// Ticks it takes to swim a block.
+    pub swim_ticks: SwimTicks,
+    /// Cost added for every door, trapdoor or fence gate opened.
+    pub open_cost: f64
 }
 
 impl Configuration {
@@ -27,9 +57,22 @@ impl Configuration {
         Configuration {
             hazard: HazardMultiplier::new(),
             cost_inf: 100_000.0,
-            timeout: Duration::from_millis(2000)
+            timeout: Duration::from_millis(2000),
+            max_health: 20.0,
+            damage_cost: 4.0,
+            corner_cutting: CornerCutting::Forbid,
+            allow_parkour: true,
+            parkour_cost: 2.0,
+            allow_breaking: false,
+            break_cost: 2.0,
+            allow_placing: false,
+            place_cost: 3.0,
+            climb_cost: ClimbCost::new(),
+            swim_ticks: SwimTicks::new(),
+            open_cost: 1.0
         }
     }
+
     /// Writes the configuration file to a given path. Will overwrite any existing configuration.
     pub fn write_config(&self, path: &Path) -> Result<()> {
         if path.exists() {
@@ -53,8 +96,108 @@ impl Configuration {
         let mut reader = BufReader::new(File::open(path)?);
         let mut in_str = String::new();
         reader.read_to_string(&mut in_str)?;
-        let config: Configuration = from_str(&in_str)?;
 
-        Ok(config)
+        Self::from_json(&in_str)
+    }
+
+    /// Parses a configuration in the same JSON as the configuration file. Like there, missing fields
+    /// are defaulted, so `{}` is the default config.
+    pub fn from_json(json: &str) -> Result<Configuration> {
+        Ok(from_str(json)?)
+    }
+}
+
+/// Cost of climbing a single block, up or down, on each kind of climbable. Measured against
+/// walking a block, which costs `1.0`.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ClimbCost {
+    pub ladder: f64,
+    /// Vines are as fast as ladders, but tend to have gaps and need a wall behind them.
+    pub vine: f64,
+    pub scaffolding: f64
+}
+
+impl ClimbCost {
+    pub fn new() -> ClimbCost {
+        ClimbCost {
+            ladder: 1.8,
+            vine: 2.2,
+            scaffolding: 1.4
+        }
+    }
+
+    /// Returns the cost of climbing a block of `climb`.
+    pub fn of(&self, climb: Climb) -> f64 {
+        match climb {
+            Climb::Ladder => self.ladder,
+            Climb::Vine => self.vine,
+            Climb::Scaffolding => self.scaffolding
+        }
+    }
+}
+
+impl Default for ClimbCost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Ticks it takes to swim a single block. These are turned into move costs the same way mining time
+/// is, so walking a block takes `agent::TICKS_PER_BLOCK`.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SwimTicks {
+    /// Swimming along the surface, head above the water.
+    pub surface: f64,
+    /// Swimming with the head underwater, which also uses up air.
+    pub diving: f64,
+    /// Added when swimming into flowing water. The direction of the current isn't known, so this
+    /// is paid whichever way it goes.
+    pub current: f64
+}
+
+impl SwimTicks {
+    pub fn new() -> SwimTicks {
+        SwimTicks {
+            surface: 9.0,
+            diving: 11.0,
+            current: 4.0
+        }
+    }
+}
+
+impl Default for SwimTicks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Rules for diagonal moves passing by blocked corners. A diagonal move squeezes between the two
+/// cardinal neighbors it cuts across, so whether it's allowed depends on what's in them.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum CornerCutting {
+    /// Never cut a corner, both sides have to be clear.
+    Forbid,
+    /// Cut a corner as long as one of the sides is clear.
+    AllowOneSide,
+    /// Always cut corners, even between two blocked sides.
+    Allow
+}
+
+impl CornerCutting {
+    /// Returns whether a diagonal move is allowed, given whether each of the sides it cuts across
+    /// is blocked.
+    pub fn allows(&self, first_blocked: bool, second_blocked: bool) -> bool {
+        match self {
+            CornerCutting::Forbid => !first_blocked && !second_blocked,
+            CornerCutting::AllowOneSide => !first_blocked || !second_blocked,
+            CornerCutting::Allow => true
+        }
     }
-}
\ No newline at end of file
+}
+
+impl Default for Configuration {
+    fn default() -> Self {
+        Self::new()
+    }
+}
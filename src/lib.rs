@@ -1,5 +1,5 @@
-mod pathing;
-mod config;
+pub mod pathing;
+pub mod config;
 #[cfg(test)]
 mod test;
-mod binding;
\ No newline at end of file
+pub mod binding;
@@ -1,17 +1,184 @@
-use crate::pathing::math::{Vector2i, Vector3i};
+use crate::pathing::math::{Face, Vector2i, Vector3i};
 use crate::{vec2i, vec3i};
-use std::ops::Add;
 use jni::JNIEnv;
-use jni::objects::JObject;
+use jni::objects::{JObject, JValueGen};
 use crate::binding::jni::JNICompatible;
+use crate::binding::cache;
+use crate::binding::util::{enum_constant, enum_name};
+use eyre::{eyre, Result};
 use crate::pathing::algorithm::GraphPosition;
+use std::f64::consts::SQRT_2;
 
 pub type Moveset<P>  = Vec<MoveAction<P>>;
 
+/// The kind of movement a `MoveAction` performs. A `Space` uses this to decide which checks a move
+/// has to pass, and how much it costs on top of its base cost.
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum MoveKind {
+    /// Walk to a neighbor on the same level.
+    Traverse,
+    /// Walk to a diagonal neighbor on the same level, cutting past the two neighbors in between.
+    /// Costs `sqrt(2)` times its base cost, since it covers that much more distance.
+    Diagonal,
+    /// Jump up a single block onto a neighbor.
+    Ascend,
+    /// Walk off a single block drop onto a neighbor.
+    Descend,
+    /// Walk off a ledge into a neighboring column and drop down onto whatever is below. The offset
+    /// of a fall only picks the column, the `Space` finds where it lands.
+    Fall,
+    /// Sprint-jump across a gap, landing up to four blocks away on the same level or one block
+    /// higher.
+    Parkour,
+    /// Break whatever is in the way of a neighbor and move into it. Works horizontally, and
+    /// straight down by digging out the block underfoot.
+    Tunnel,
+    /// Place a block to stand on under a neighbor, then walk onto it.
+    Bridge,
+    /// Jump straight up and place a block where the entity's feet were.
+    Pillar,
+    /// Climb a block straight up or down a ladder, vine or scaffolding.
+    Climb,
+    /// Swim a block in any direction through a liquid, either along the surface or diving.
+    Swim
+}
+
+impl MoveKind {
+    pub const ALL: [MoveKind; 11] = [
+        MoveKind::Traverse, MoveKind::Diagonal, MoveKind::Ascend, MoveKind::Descend, MoveKind::Fall,
+        MoveKind::Parkour, MoveKind::Tunnel, MoveKind::Bridge, MoveKind::Pillar, MoveKind::Climb,
+        MoveKind::Swim
+    ];
+
+    /// Name of the matching constant in the Java enum.
+    const fn java_name(&self) -> &'static str {
+        match self {
+            MoveKind::Traverse => "TRAVERSE",
+            MoveKind::Diagonal => "DIAGONAL",
+            MoveKind::Ascend => "ASCEND",
+            MoveKind::Descend => "DESCEND",
+            MoveKind::Fall => "FALL",
+            MoveKind::Parkour => "PARKOUR",
+            MoveKind::Tunnel => "TUNNEL",
+            MoveKind::Bridge => "BRIDGE",
+            MoveKind::Pillar => "PILLAR",
+            MoveKind::Climb => "CLIMB",
+            MoveKind::Swim => "SWIM"
+        }
+    }
+}
+
+impl <'local> JNICompatible<'local> for MoveKind {
+    const CLASS: &'static str = "com/genericbadname/s4mc/pathing/MoveKind";
+
+    fn to_jni(&self, env: &mut JNIEnv<'local>) -> Result<JObject<'local>> {
+        enum_constant(env, Self::CLASS, self.java_name())
+    }
+
+    fn from_jni(env: &mut JNIEnv<'local>, object: JObject<'local>) -> Result<Self>
+    where
+        Self: Sized
+    {
+        let name = enum_name(env, &object)?;
+
+        Self::ALL.into_iter()
+            .find(|kind| kind.java_name() == name)
+            .ok_or_else(|| eyre!("Unknown move kind {}", name))
+    }
+}
+
+/// Something the entity has to do to the world, on top of moving, to perform a move.
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum Interaction<P> where P: GraphPosition {
+    /// Break the block at a position.
+    Break(P),
+    /// Place a block at `pos`, against the block on its `face` side.
+    Place {
+        pos: P,
+        face: Face
+    },
+    /// Open the door, trapdoor or fence gate at a position. For doors, this is always the lower
+    /// half.
+    Open(P)
+}
+
+/// Java enum telling the kinds of `Interaction` apart, as Java has no enums with data.
+pub(crate) const INTERACTION_KIND_CLASS: &str = "com/genericbadname/s4mc/pathing/InteractionKind";
+
+/// An `Interaction` is a record of its kind, its position and, only for placements, the face the
+/// block is placed against (`null` otherwise).
+impl <'local, P> JNICompatible<'local> for Interaction<P> where P: GraphPosition + JNICompatible<'local> {
+    const CLASS: &'static str = "com/genericbadname/s4mc/pathing/Interaction";
+
+    fn to_jni(&self, env: &mut JNIEnv<'local>) -> Result<JObject<'local>> {
+        let (kind, pos, face) = match self {
+            Interaction::Break(pos) => ("BREAK", pos, None),
+            Interaction::Place { pos, face } => ("PLACE", pos, Some(face)),
+            Interaction::Open(pos) => ("OPEN", pos, None)
+        };
+        let kind = enum_constant(env, INTERACTION_KIND_CLASS, kind)?;
+        let pos = pos.to_jni(env)?;
+        let face = match face {
+            Some(face) => face.to_jni(env)?,
+            None => JObject::null()
+        };
+
+        let sig = format!("(L{};L{};L{};)V", INTERACTION_KIND_CLASS, P::CLASS, Face::CLASS);
+        cache::new_object(env, Self::CLASS, &sig, &[
+            JValueGen::Object(&kind),
+            JValueGen::Object(&pos),
+            JValueGen::Object(&face)
+        ])
+    }
+
+    fn from_jni(env: &mut JNIEnv<'local>, object: JObject<'local>) -> Result<Self>
+    where
+        Self: Sized
+    {
+        let kind_sig = format!("()L{};", INTERACTION_KIND_CLASS);
+        let kind = cache::call_method(env, &object, Self::CLASS, "kind", &kind_sig, &[])?.l()?;
+        let kind = enum_name(env, &kind)?;
+        let pos_sig = format!("()L{};", P::CLASS);
+        let pos = cache::call_method(env, &object, Self::CLASS, "pos", &pos_sig, &[])?.l()?;
+        let pos = P::from_jni(env, pos)?;
+
+        match kind.as_str() {
+            "BREAK" => Ok(Interaction::Break(pos)),
+            "PLACE" => {
+                let face_sig = format!("()L{};", Face::CLASS);
+                let face = cache::call_method(env, &object, Self::CLASS, "face", &face_sig, &[])?.l()?;
+                if face.is_null() { return Err(eyre!("Placement at {:?} has no face", pos)) }
+                Ok(Interaction::Place { pos, face: Face::from_jni(env, face)? })
+            }
+            "OPEN" => Ok(Interaction::Open(pos)),
+            _ => Err(eyre!("Unknown interaction kind {}", kind))
+        }
+    }
+}
+
+/// Farthest a parkour jump can land on the same level, in blocks.
+pub const MAX_PARKOUR_DISTANCE: i32 = 4;
+/// Farthest a parkour jump can land a block higher, in blocks.
+pub const MAX_PARKOUR_ASCEND_DISTANCE: i32 = 3;
+
+/// Highest drop that doesn't hurt, in blocks.
+pub const SAFE_FALL_HEIGHT: i32 = 3;
+/// Cost of every block dropped during a fall. Falling is quicker than walking, but pricing it any
+/// lower would let straight-line heuristics overestimate long drops.
+pub const FALL_COST_PER_BLOCK: f64 = 1.0;
+
+/// Returns the damage taken from dropping `height` blocks onto a block with the given fall damage
+/// multiplier. Every block past `SAFE_FALL_HEIGHT` deals a point of damage.
+pub fn fall_damage(height: i32, multiplier: f64) -> f64 {
+    (height - SAFE_FALL_HEIGHT).max(0) as f64 * multiplier
+}
+
 /// A move action that can be taken by the pathfinding entity. These are the "lines" that
 /// connect nodes on the graph.
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
 pub struct MoveAction<P> where P: GraphPosition {
+    /// What kind of movement this is.
+    pub kind: MoveKind,
     /// Initial cost to execute this move action.
     pub cost: u64,
     /// Offset from the current position to check for this action (the neighbor position).
@@ -19,8 +186,21 @@ pub struct MoveAction<P> where P: GraphPosition {
 }
 
 impl <P> MoveAction<P> where P: GraphPosition {
+    /// Creates a `MoveKind::Traverse` action.
     pub const fn new(cost: u64, offset: P) -> Self {
-        Self { cost, offset }
+        Self::of_kind(MoveKind::Traverse, cost, offset)
+    }
+
+    pub const fn of_kind(kind: MoveKind, cost: u64, offset: P) -> Self {
+        Self { kind, cost, offset }
+    }
+
+    /// Returns the cost of this action before anything about the space it's taken in is known.
+    pub fn base_cost(&self) -> f64 {
+        match self.kind {
+            MoveKind::Diagonal => self.cost as f64 * SQRT_2,
+            _ => self.cost as f64
+        }
     }
 }
 
@@ -44,30 +224,132 @@ impl <P> SpatialAction<P> where P: GraphPosition {
     }
 }
 
-impl <'local, P> JNICompatible<'local> for SpatialAction<P> where P: GraphPosition {
+/// A `SpatialAction` is a record of its position, and the kind, base cost and offset of the move
+/// that led there. All but the position are `null` (or `0`) for the root action.
+impl <'local, P> JNICompatible<'local> for SpatialAction<P> where P: GraphPosition + JNICompatible<'local> {
     const CLASS: &'static str = "com/genericbadname/s4mc/pathing/SpatialAction";
 
-    fn to_jni(&self, env: &mut JNIEnv<'local>) -> eyre::Result<JObject<'local>> {
-        todo!()
+    fn to_jni(&self, env: &mut JNIEnv<'local>) -> Result<JObject<'local>> {
+        let pos = self.pos.to_jni(env)?;
+        let (kind, cost, offset) = match &self.move_action {
+            Some(action) => (action.kind.to_jni(env)?, action.cost as i64, action.offset.to_jni(env)?),
+            None => (JObject::null(), 0, JObject::null())
+        };
+
+        let sig = format!("(L{pos};L{};JL{pos};)V", MoveKind::CLASS, pos = P::CLASS);
+        cache::new_object(env, Self::CLASS, &sig, &[
+            JValueGen::Object(&pos),
+            JValueGen::Object(&kind),
+            JValueGen::Long(cost),
+            JValueGen::Object(&offset)
+        ])
     }
 
-    fn from_jni(env: &mut JNIEnv<'local>, object: JObject<'local>) -> eyre::Result<Self>
+    fn from_jni(env: &mut JNIEnv<'local>, object: JObject<'local>) -> Result<Self>
     where
         Self: Sized
     {
-        todo!()
+        let pos_sig = format!("()L{};", P::CLASS);
+        let pos = cache::call_method(env, &object, Self::CLASS, "pos", &pos_sig, &[])?.l()?;
+        let pos = P::from_jni(env, pos)?;
+        let kind_sig = format!("()L{};", MoveKind::CLASS);
+        let kind = cache::call_method(env, &object, Self::CLASS, "kind", &kind_sig, &[])?.l()?;
+        if kind.is_null() { return Ok(Self::new_root(pos)) }
+
+        let kind = MoveKind::from_jni(env, kind)?;
+        let cost = cache::call_method(env, &object, Self::CLASS, "cost", "()J", &[])?.j()?;
+        let cost = u64::try_from(cost).map_err(|_| eyre!("Move cost {} is negative", cost))?;
+        let offset = cache::call_method(env, &object, Self::CLASS, "offset", &pos_sig, &[])?.l()?;
+        if offset.is_null() { return Err(eyre!("{:?} move to {:?} has no offset", kind, pos)) }
+
+        Ok(Self::new(pos, MoveAction::of_kind(kind, cost, P::from_jni(env, offset)?)))
     }
 }
 
 pub fn default_moveset() -> Moveset<Vector3i> {
-    vec![
+    let mut moves = vec![
         MoveAction::new(1, vec3i!(1, 0, 0)),
         MoveAction::new(1, vec3i!(-1, 0, 0)),
         MoveAction::new(1, vec3i!(0, 0, 1)),
-        MoveAction::new(1, vec3i!(0, 0, -1))
+        MoveAction::new(1, vec3i!(0, 0, -1)),
+
+        MoveAction::of_kind(MoveKind::Diagonal, 1, vec3i!(1, 0, 1)),
+        MoveAction::of_kind(MoveKind::Diagonal, 1, vec3i!(1, 0, -1)),
+        MoveAction::of_kind(MoveKind::Diagonal, 1, vec3i!(-1, 0, 1)),
+        MoveAction::of_kind(MoveKind::Diagonal, 1, vec3i!(-1, 0, -1)),
+
+        MoveAction::of_kind(MoveKind::Ascend, 2, vec3i!(1, 1, 0)),
+        MoveAction::of_kind(MoveKind::Ascend, 2, vec3i!(-1, 1, 0)),
+        MoveAction::of_kind(MoveKind::Ascend, 2, vec3i!(0, 1, 1)),
+        MoveAction::of_kind(MoveKind::Ascend, 2, vec3i!(0, 1, -1)),
+
+        MoveAction::of_kind(MoveKind::Descend, 1, vec3i!(1, -1, 0)),
+        MoveAction::of_kind(MoveKind::Descend, 1, vec3i!(-1, -1, 0)),
+        MoveAction::of_kind(MoveKind::Descend, 1, vec3i!(0, -1, 1)),
+        MoveAction::of_kind(MoveKind::Descend, 1, vec3i!(0, -1, -1)),
+
+        MoveAction::of_kind(MoveKind::Fall, 1, vec3i!(1, 0, 0)),
+        MoveAction::of_kind(MoveKind::Fall, 1, vec3i!(-1, 0, 0)),
+        MoveAction::of_kind(MoveKind::Fall, 1, vec3i!(0, 0, 1)),
+        MoveAction::of_kind(MoveKind::Fall, 1, vec3i!(0, 0, -1))
+    ];
+    moves.extend(parkour_moves());
+    moves.extend(tunnel_moves());
+    moves.extend(placement_moves());
+    moves.push(MoveAction::of_kind(MoveKind::Climb, 1, vec3i!(0, 1, 0)));
+    moves.push(MoveAction::of_kind(MoveKind::Climb, 1, vec3i!(0, -1, 0)));
+    moves.extend(swim_moves());
+    moves
+}
+
+/// Swims in all six directions.
+pub fn swim_moves() -> Moveset<Vector3i> {
+    [vec3i!(1, 0, 0), vec3i!(-1, 0, 0), vec3i!(0, 0, 1), vec3i!(0, 0, -1), vec3i!(0, 1, 0), vec3i!(0, -1, 0)]
+        .into_iter()
+        .map(|offset| MoveAction::of_kind(MoveKind::Swim, 1, offset))
+        .collect()
+}
+
+/// Bridges in every cardinal direction, and a pillar straight up.
+pub fn placement_moves() -> Moveset<Vector3i> {
+    vec![
+        MoveAction::of_kind(MoveKind::Bridge, 1, vec3i!(1, 0, 0)),
+        MoveAction::of_kind(MoveKind::Bridge, 1, vec3i!(-1, 0, 0)),
+        MoveAction::of_kind(MoveKind::Bridge, 1, vec3i!(0, 0, 1)),
+        MoveAction::of_kind(MoveKind::Bridge, 1, vec3i!(0, 0, -1)),
+        MoveAction::of_kind(MoveKind::Pillar, 2, vec3i!(0, 1, 0))
     ]
 }
 
+/// Tunnels in every cardinal direction and straight down.
+pub fn tunnel_moves() -> Moveset<Vector3i> {
+    vec![
+        MoveAction::of_kind(MoveKind::Tunnel, 1, vec3i!(1, 0, 0)),
+        MoveAction::of_kind(MoveKind::Tunnel, 1, vec3i!(-1, 0, 0)),
+        MoveAction::of_kind(MoveKind::Tunnel, 1, vec3i!(0, 0, 1)),
+        MoveAction::of_kind(MoveKind::Tunnel, 1, vec3i!(0, 0, -1)),
+        MoveAction::of_kind(MoveKind::Tunnel, 1, vec3i!(0, -1, 0))
+    ]
+}
+
+/// Parkour jumps in every cardinal direction, for every distance they can cover. Each costs the
+/// distance it covers, plus one if it also climbs a block.
+pub fn parkour_moves() -> Moveset<Vector3i> {
+    let directions = [vec3i!(1, 0, 0), vec3i!(-1, 0, 0), vec3i!(0, 0, 1), vec3i!(0, 0, -1)];
+    let mut moves = Vec::new();
+    for dir in directions {
+        for distance in 2..=MAX_PARKOUR_DISTANCE {
+            let offset = vec3i!(dir.x * distance, 0, dir.z * distance);
+            moves.push(MoveAction::of_kind(MoveKind::Parkour, distance as u64, offset));
+            if distance <= MAX_PARKOUR_ASCEND_DISTANCE {
+                moves.push(MoveAction::of_kind(MoveKind::Parkour, distance as u64 + 1, offset + vec3i!(0, 1, 0)));
+            }
+        }
+    }
+
+    moves
+}
+
 pub fn moveset_2d_cardinal() -> Moveset<Vector2i> {
     vec![
         MoveAction::new(1, vec2i!(1, 0)),
@@ -77,11 +359,27 @@ pub fn moveset_2d_cardinal() -> Moveset<Vector2i> {
     ]
 }
 
+/// Cardinal and diagonal moves on a plane.
+pub fn moveset_2d_diagonal() -> Moveset<Vector2i> {
+    let mut moves = moveset_2d_cardinal();
+    moves.extend([
+        Moveset2D::UpLeft.of(),
+        Moveset2D::UpRight.of(),
+        Moveset2D::DownLeft.of(),
+        Moveset2D::DownRight.of()
+    ]);
+    moves
+}
+
 pub enum Moveset2D {
     Left,
     Right,
     Up,
-    Down
+    Down,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight
 }
 
 impl Moveset2D {
@@ -90,13 +388,17 @@ impl Moveset2D {
             Moveset2D::Left => MoveAction::new(1, vec2i!(-1, 0)),
             Moveset2D::Right => MoveAction::new(1, vec2i!(1, 0)),
             Moveset2D::Up => MoveAction::new(1, vec2i!(0, -1)),
-            Moveset2D::Down => MoveAction::new(1, vec2i!(0, 1))
+            Moveset2D::Down => MoveAction::new(1, vec2i!(0, 1)),
+            Moveset2D::UpLeft => MoveAction::of_kind(MoveKind::Diagonal, 1, vec2i!(-1, -1)),
+            Moveset2D::UpRight => MoveAction::of_kind(MoveKind::Diagonal, 1, vec2i!(1, -1)),
+            Moveset2D::DownLeft => MoveAction::of_kind(MoveKind::Diagonal, 1, vec2i!(-1, 1)),
+            Moveset2D::DownRight => MoveAction::of_kind(MoveKind::Diagonal, 1, vec2i!(1, 1))
         }
     }
 }
 
-impl Into<MoveAction<Vector2i>> for Moveset2D {
-    fn into(self) -> MoveAction<Vector2i> {
-        self.of()
+impl From<Moveset2D> for MoveAction<Vector2i> {
+    fn from(value: Moveset2D) -> Self {
+        value.of()
     }
 }
\ No newline at end of file
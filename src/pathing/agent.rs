@@ -0,0 +1,184 @@
+use crate::binding::cache;
+use crate::binding::jni::JNICompatible;
+use crate::pathing::block::{BlockInfo, ToolKind};
+use eyre::{eyre, Result};
+use jni::objects::{JObject, JValueGen};
+use jni::JNIEnv;
+
+/// Game ticks it takes to walk a block. Used to put time spent doing anything else (like mining)
+/// in terms of walking distance, which is what move costs are measured in.
+pub const TICKS_PER_BLOCK: f64 = 4.633;
+
+/// Ticks a player can stay underwater before starting to drown.
+pub const MAX_AIR: u32 = 300;
+
+/// A tool held by the entity.
+#[derive(Debug, Copy, Clone, PartialEq, JNICompatible)]
+#[jni(class = "com/genericbadname/s4mc/pathing/Tool")]
+pub struct Tool {
+    /// What kind of blocks the tool is good at.
+    pub kind: ToolKind,
+    /// Mining speed on blocks the tool is good at, e.g. `8.0` for a diamond pickaxe. Anything else
+    /// is mined at the speed of a bare hand, `1.0`.
+    pub speed: f64
+}
+
+impl Tool {
+    pub const fn new(kind: ToolKind, speed: f64) -> Tool {
+        Tool { kind, speed }
+    }
+}
+
+/// Size of the entity's bounding box, in blocks.
+#[derive(Debug, Copy, Clone, PartialEq, JNICompatible)]
+#[jni(class = "com/genericbadname/s4mc/pathing/Hitbox")]
+pub struct Hitbox {
+    /// Size along both X and Z.
+    pub width: f64,
+    pub height: f64
+}
+
+impl Hitbox {
+    pub const PLAYER: Hitbox = Hitbox::new(0.6, 1.8);
+
+    pub const fn new(width: f64, height: f64) -> Hitbox {
+        Hitbox { width, height }
+    }
+
+    /// Returns how many blocks the entity takes up along X and Z. Its position is the corner of
+    /// that footprint with the lowest coordinates.
+    pub fn width_blocks(&self) -> i32 {
+        (self.width.ceil() as i32).max(1)
+    }
+
+    /// Returns how many blocks tall of a space the entity needs to fit through.
+    pub fn height_blocks(&self) -> i32 {
+        (self.height.ceil() as i32).max(1)
+    }
+}
+
+impl Default for Hitbox {
+    fn default() -> Self {
+        Self::PLAYER
+    }
+}
+
+/// The entity a path is calculated for, and whatever it carries that changes how it can move.
+/// Unlike the world, this can be different for every request.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Agent {
+    /// Size of the entity, which decides how much room every move needs.
+    pub hitbox: Hitbox,
+    /// Tool held in hand, if any.
+    pub tool: Option<Tool>,
+    /// Blocks the entity is willing to place to bridge gaps and pillar up, like dirt or
+    /// cobblestone.
+    pub throwaway_blocks: u32,
+    /// Ticks the entity can hold its breath for, `MAX_AIR` unless it has water breathing or
+    /// respiration of some kind.
+    pub air_supply: u32,
+    /// Whether the entity is immune to fire. Lava is never entered without it.
+    pub fire_resistant: bool
+}
+
+impl Default for Agent {
+    fn default() -> Self {
+        Agent {
+            hitbox: Hitbox::PLAYER,
+            tool: None,
+            throwaway_blocks: 0,
+            air_supply: MAX_AIR,
+            fire_resistant: false
+        }
+    }
+}
+
+/// An `Agent` is a record like the struct, except that Java has no unsigned integers, so negative
+/// amounts are rejected.
+impl <'local> JNICompatible<'local> for Agent {
+    const CLASS: &'static str = "com/genericbadname/s4mc/pathing/Agent";
+
+    fn to_jni(&self, env: &mut JNIEnv<'local>) -> Result<JObject<'local>> {
+        let hitbox = self.hitbox.to_jni(env)?;
+        let tool = self.tool.to_jni(env)?;
+        let sig = format!("({}{}IIZ)V", Hitbox::signature(), Tool::signature());
+        cache::new_object(env, Self::CLASS, &sig, &[
+            JValueGen::Object(&hitbox),
+            JValueGen::Object(&tool),
+            JValueGen::Int(i32::try_from(self.throwaway_blocks)?),
+            JValueGen::Int(i32::try_from(self.air_supply)?),
+            JValueGen::Bool(self.fire_resistant.into())
+        ])
+    }
+
+    fn from_jni(env: &mut JNIEnv<'local>, object: JObject<'local>) -> Result<Self>
+    where
+        Self: Sized
+    {
+        let hitbox_sig = format!("(){}", Hitbox::signature());
+        let hitbox = cache::call_method(env, &object, Self::CLASS, "hitbox", &hitbox_sig, &[])?.l()?;
+        let tool_sig = format!("(){}", Tool::signature());
+        let tool = cache::call_method(env, &object, Self::CLASS, "tool", &tool_sig, &[])?.l()?;
+        let throwaway_blocks = cache::call_method(env, &object, Self::CLASS, "throwawayBlocks", "()I", &[])?.i()?;
+        let air_supply = cache::call_method(env, &object, Self::CLASS, "airSupply", "()I", &[])?.i()?;
+        let fire_resistant = cache::call_method(env, &object, Self::CLASS, "fireResistant", "()Z", &[])?.z()?;
+
+        Ok(Agent {
+            hitbox: Hitbox::from_jni(env, hitbox)?,
+            tool: Option::from_jni(env, tool)?,
+            throwaway_blocks: u32::try_from(throwaway_blocks)
+                .map_err(|_| eyre!("Throwaway block count {} is negative", throwaway_blocks))?,
+            air_supply: u32::try_from(air_supply).map_err(|_| eyre!("Air supply {} is negative", air_supply))?,
+            fire_resistant
+        })
+    }
+}
+
+impl Agent {
+    /// Creates an empty-handed agent.
+    pub fn new() -> Agent {
+        Agent::default()
+    }
+
+    pub fn with_hitbox(mut self, hitbox: Hitbox) -> Agent {
+        self.hitbox = hitbox;
+        self
+    }
+
+    pub fn with_tool(mut self, tool: Tool) -> Agent {
+        self.tool = Some(tool);
+        self
+    }
+
+    pub fn with_throwaway_blocks(mut self, throwaway_blocks: u32) -> Agent {
+        self.throwaway_blocks = throwaway_blocks;
+        self
+    }
+
+    pub fn with_air_supply(mut self, air_supply: u32) -> Agent {
+        self.air_supply = air_supply;
+        self
+    }
+
+    pub fn with_fire_resistance(mut self, fire_resistant: bool) -> Agent {
+        self.fire_resistant = fire_resistant;
+        self
+    }
+
+    /// Returns the amount of ticks it takes to break a block, or `Option::None` if it can't be
+    /// broken at all. Follows the vanilla formula: every tick deals `speed / hardness` damage to
+    /// the block, divided by 30 if it can be harvested and 100 if not, until it reaches 1.
+    pub fn break_ticks(&self, info: &BlockInfo) -> Option<f64> {
+        let hardness = info.hardness?;
+        if hardness <= 0.0 { return Some(0.0) }
+
+        let tool = self.tool.filter(|tool| info.tool == Some(tool.kind));
+        let speed = tool.map_or(1.0, |tool| tool.speed);
+        let harvestable = !info.requires_tool || tool.is_some();
+        let damage = speed / hardness / if harvestable { 30.0 } else { 100.0 };
+
+        // enough damage in a single tick breaks the block instantly
+        if damage >= 1.0 { return Some(0.0) }
+        Some((1.0 / damage).ceil())
+    }
+}
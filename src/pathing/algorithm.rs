@@ -1,11 +1,14 @@
 use std::fmt::Debug;
 use crate::config::Configuration;
-use crate::pathing::action::{Moveset, SpatialAction};
-use crate::pathing::data::{BinaryHeapOpenSet, Node, PathNode};
+use crate::pathing::action::{Interaction, Moveset, SpatialAction};
+use crate::pathing::agent::Agent;
+use crate::pathing::data::{BinaryHeapOpenSet, Node, NodeIndexMap, PathNode, PathResult, PathState};
+use crate::pathing::goal::{Goal, GoalExact};
 use crate::pathing::world::Space;
-use dashmap::DashMap;
 use std::hash::Hash;
 use std::ops::Add;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Instant;
 use eyre::{eyre, OptionExt, Result};
 use log::warn;
@@ -15,19 +18,38 @@ const MINIMUM_IMPROVEMENT: f64 = 0.01;
 /// Temporary state for A* path calculations. The type `P` is used as the "position in space" when
 /// finding nodes. Any kind of cheap and easily copyable data can be used, as the A* algorithm does
 /// not care for dimensionality, only relationships between nodes.
+///
+/// Nodes are identified by `P` alone, so the same position reached through different moves is the
+/// same node. If two positions should be distinct search states despite sharing coordinates (e.g.
+/// standing vs. crouching), that extra state has to be part of `P` itself. Resources used up along
+/// the way (see `PathState`) are carried by nodes, but don't tell them apart.
 pub struct PathCalculator<P, S> where P: GraphPosition, S: Space<P> {
     /// The Open set (min-heap) involved in the calculation. These are nodes that have not been
     /// considered yet, but are known (the "node frontier").
-    open_set: BinaryHeapOpenSet<P>,
-    /// The Closed set involved in the calculation. These are nodes that have already been
-    /// considered and do not need to be re-examined.
-    closed_set: DashMap<SpatialAction<P>, Node<P>>,
+    open_set: BinaryHeapOpenSet,
+    /// Arena of every node discovered so far. Nodes refer to each other (and are referred to by the
+    /// Open Set) by their index in here. Nodes that are not in the Open Set have already been
+    /// considered (they are Closed).
+    nodes: Vec<Node<P>>,
+    /// Arena index of every discovered node, keyed by its packed search state.
+    node_map: NodeIndexMap,
+    /// What has to be done to the world for the moves nodes were last reached by. Nodes refer to
+    /// these by index, so that they stay `Copy`.
+    interactions: Vec<Vec<Interaction<P>>>,
     /// The pathfinder's allowed moves.
     moves: Moveset<P>,
     /// The `Space` that this pathfinder will sample from.
     space: Box<S>,
     /// General configuration values for the pathfinding system.
-    config: Configuration
+    config: Configuration,
+    /// The entity paths are calculated for.
+    agent: Agent,
+    /// Checked every iteration to stop a calculation early, from another thread.
+    cancel: CancelToken,
+    /// Arena index of the node closest to the goal found so far, used to build partial paths.
+    best_node: Option<u32>,
+    /// Amount of nodes expanded during the last calculation.
+    expanded: usize
 }
 
 impl <P, S> PathCalculator<P, S> where P: GraphPosition, S: Space<P> {
@@ -35,104 +57,163 @@ impl <P, S> PathCalculator<P, S> where P: GraphPosition, S: Space<P> {
         // TODO: make index bound equal to the maximum amount of Voxels within a Chunk/region of Chunks
         PathCalculator {
             open_set: BinaryHeapOpenSet::new(),
-            closed_set: DashMap::new(),
+            nodes: Vec::with_capacity(1024),
+            node_map: NodeIndexMap::with_capacity(1024),
+            interactions: Vec::new(),
             moves,
             space,
-            config
+            config,
+            agent: Agent::new(),
+            cancel: CancelToken::new(),
+            best_node: None,
+            expanded: 0
         }
     }
 
     /// Calculates the optimal path from a start position to an end position.
-    pub fn calculate(&mut self, start: P, end: P) -> Result<Vec<PathNode<P>>> {
+    pub fn calculate(&mut self, start: P, end: P) -> Result<PathResult<P>> {
+        self.calculate_to_goal(start, &GoalExact(end))
+    }
+
+    /// Calculates the optimal path from a start position to any position satisfying `goal`. If the
+    /// configured timeout is reached first, or the goal can't be reached at all, the path to the
+    /// node closest to the goal is returned as a partial path instead.
+    pub fn calculate_to_goal(&mut self, start: P, goal: &dyn Goal<P>) -> Result<PathResult<P>> {
         let start_time = Instant::now();
-        let mut start_node = Node::start_node(start, &end);
+        // whatever the last calculation left behind is of no use to this one
+        self.reset();
+        let start_state = PathState::new(&self.agent);
+        let start_idx = self.push_node(Node::start_node(start, goal.heuristic(start), start_state))?;
+        self.open_set.insert(&mut self.nodes, start_idx)?;
+        self.best_node = Some(start_idx);
 
-        self.closed_set.insert(SpatialAction::new_root(start), start_node);
-        self.open_set.insert(&mut start_node)?;
+        // run until all nodes are considered, time is up or someone else wants it to stop
+        while let Some(c_idx) = self.open_set.pop(&mut self.nodes) {
+            if self.cancel.is_cancelled() {
+                return Ok(PathResult::cancelled());
+            }
 
-        // run until all nodes are considered or time is up
-        while !self.open_set.is_empty() {
-            if let Some(c_node) = self.open_set.pop() {
-                // if at the end, exit early
-                if c_node.action.pos == end {
-                    return self.retrace(c_node);
-                }
-                // otherwise run an iteration and update
-                self.update_positions(c_node, &end)?;
+            let c_node = self.nodes[c_idx as usize];
+            // if at the end, exit early
+            if goal.is_goal(c_node.action.pos) {
+                return Ok(PathResult::complete(self.retrace(c_idx)?));
             }
+            // otherwise run an iteration and update
+            self.expanded += 1;
+            self.update_positions(c_node, c_idx, goal)?;
 
             if start_time.elapsed().ge(&self.config.timeout) {
-                warn!("Pathfinder timed out.");
-                //break
+                warn!("Pathfinder timed out after {} ms.", start_time.elapsed().as_millis());
+                return self.best_partial();
             }
         }
 
-        // no path found, return an empty one
-        Ok(vec![])
+        // every reachable node was considered, so this is as close as it gets
+        self.best_partial()
+    }
+
+    /// Retraces the path to the best node found so far, or fails if no node got any closer to
+    /// the goal than the start.
+    fn best_partial(&self) -> Result<PathResult<P>> {
+        match self.best_node {
+            // the start is the only root, and a path that stays there gets nowhere
+            Some(best) if self.nodes[best as usize].parent.is_some() => Ok(PathResult::partial(self.retrace(best)?)),
+            _ => Ok(PathResult::failed())
+        }
     }
 
     /// Updates node positions and advances the pathfinding algorithm.
-    fn update_positions(&mut self, current: Node<P>, end: &P) -> Result<()> {
+    fn update_positions(&mut self, current: Node<P>, current_idx: u32, goal: &dyn Goal<P>) -> Result<()> {
         // moves determine which neighbor to check
-        for action in self.moves.iter() {
-            let neighbor_pos = current.action.pos + action.offset;
+        for move_idx in 0..self.moves.len() {
+            let action = self.moves[move_idx];
+            let Some(result) = self.space.evaluate(current.action.pos, &action, &self.agent, &current.state) else { continue };
+            let neighbor_pos = result.dest;
+            let tentative_g_cost = current.g_cost + result.cost;
 
-            let material_cost = self.space.material_cost(neighbor_pos);
-            let tentative_g_cost = current.g_cost + (action.cost as f64) + material_cost;
+            // undiscovered nodes start out at infinite cost, so there's no point in creating one
+            // that can't be improved
+            let existing = self.node_map.get(neighbor_pos.pack());
+            if existing.is_none() && self.config.cost_inf - tentative_g_cost <= MINIMUM_IMPROVEMENT {
+                continue
+            }
+            let neighbor_idx = match existing {
+                Some(idx) => idx,
+                None => self.push_node(Node {
+                    g_cost: self.config.cost_inf,
+                    h_cost: goal.heuristic(neighbor_pos),
+                    action: SpatialAction::new_root(neighbor_pos),
+                    parent: None,
+                    heap_idx: None,
+                    state: current.state,
+                    interactions: None
+                })?
+            };
 
-            // handle the different types of references
-            let action_to = SpatialAction::new(neighbor_pos, *action);
-            let mut neighbor = self.get_node_at(&action_to, &current, end)
-                .ok_or_eyre("Couldn't get a node from the Closed Set")?;
+            let neighbor = &mut self.nodes[neighbor_idx as usize];
             // if this neighbor is better than the current, add it to the open set
             if neighbor.g_cost - tentative_g_cost > MINIMUM_IMPROVEMENT {
-                neighbor.parent = Some(current.action);
+                neighbor.action = SpatialAction::new(neighbor_pos, action);
+                neighbor.parent = Some(current_idx);
                 neighbor.g_cost = tentative_g_cost;
-                neighbor.h_cost = neighbor_pos.distance_to(end);
+                neighbor.state = result.state.unwrap_or(current.state);
+                // the move is kept, so what it takes to perform it has to be kept along with it
+                neighbor.interactions = match (neighbor.interactions, result.interactions.is_empty()) {
+                    (_, true) => None,
+                    (Some(idx), false) => {
+                        self.interactions[idx as usize] = result.interactions;
+                        Some(idx)
+                    }
+                    (None, false) => {
+                        let idx = u32::try_from(self.interactions.len())
+                            .map_err(|_| eyre!("Interaction arena is full"))?;
+                        self.interactions.push(result.interactions);
+                        Some(idx)
+                    }
+                };
+                let is_open = neighbor.is_open();
 
-                // decrease key of the open set
-                if neighbor.is_open() {
-                    self.open_set.sift_up(&mut neighbor)?;
+                let neighbor = *neighbor;
+                if self.best_node.is_none_or(|best| neighbor.closer_than(&self.nodes[best as usize])) {
+                    self.best_node = Some(neighbor_idx);
+                }
+
+                // decrease key of the open set, or reopen the node
+                if is_open {
+                    self.open_set.sift_up(&mut self.nodes, neighbor_idx)?;
                 } else {
-                    self.open_set.insert(&mut neighbor)?;
+                    self.open_set.insert(&mut self.nodes, neighbor_idx)?;
                 }
             }
-
-            // update parent
-            self.closed_set.insert(action_to, neighbor);
         }
 
         Ok(())
     }
 
-    /// Tries to get the node at the specified position `pos`. Depending on the cost of this node,
-    /// it may be added to the Open Set or stay Closed.
-    fn get_node_at(&self, action: &SpatialAction<P>, current: &Node<P>, end: &P) -> Option<Node<P>> {
-        if !self.closed_set.contains_key(action) {
-            // create a new closed node if one doesn't exist at that position.
-            let new_closed = Node {
-                g_cost: self.config.cost_inf,
-                h_cost: action.pos.distance_to(end),
-                parent: Some(current.action),
-                action: *action,
-                heap_idx: None,
-            };
-            self.closed_set.insert(*action, new_closed);
-        }
-
-        Some(*self.closed_set.get(action)?.value())
+    /// Adds a node to the arena, returning its index.
+    fn push_node(&mut self, node: Node<P>) -> Result<u32> {
+        let idx = u32::try_from(self.nodes.len())
+            .map_err(|_| eyre!("Node arena is full"))?;
+        self.node_map.insert(node.action.pos.pack(), idx);
+        self.nodes.push(node);
+        Ok(idx)
     }
 
     /// Retraces the `Node` relationships to find the optimal path.
-    fn retrace(&self, end: Node<P>) -> Result<Vec<PathNode<P>>> {
+    fn retrace(&self, end: u32) -> Result<Vec<PathNode<P>>> {
         let mut path: Vec<PathNode<P>> = Vec::new();
-        path.push(PathNode::new(end.action));
-        let mut next: Option<SpatialAction<P>> = end.parent;
+        let mut next: Option<u32> = Some(end);
 
         while let Some(current) = next {
-            let current_node= self.closed_set.get(&current)
-                .ok_or_eyre("Node had a dangling parent (SpatialAction did not exist)")?;
-            path.push(PathNode::new(current_node.action));
+            let current_node = self.nodes.get(current as usize)
+                .ok_or_eyre("Node had a dangling parent (index did not exist)")?;
+            let interactions = match current_node.interactions {
+                Some(idx) => self.interactions.get(idx as usize)
+                    .ok_or_eyre("Node had dangling interactions (index did not exist)")?
+                    .clone(),
+                None => Vec::new()
+            };
+            path.push(PathNode::with_interactions(current_node.action, interactions));
             next = current_node.parent;
         }
 
@@ -140,17 +221,71 @@ impl <P, S> PathCalculator<P, S> where P: GraphPosition, S: Space<P> {
         Ok(path)
     }
 
-    /// Resets the calculator for reuse. Not strictly necessary, this is mainly for continually
-    /// calculated on the same entity and preserving its moveset/config.
+    /// Sets the entity paths are calculated for. Stays in place across calculations until changed.
+    pub fn set_agent(&mut self, agent: Agent) {
+        self.agent = agent;
+    }
+
+    /// Returns the entity paths are calculated for.
+    pub fn agent(&self) -> &Agent {
+        &self.agent
+    }
+
+    /// Sets the token checked for cancellation. Stays in place across calculations until changed,
+    /// so a token that has been cancelled has to be replaced before calculating again.
+    pub fn set_cancel_token(&mut self, cancel: CancelToken) {
+        self.cancel = cancel;
+    }
+
+    /// Returns the `Space` this pathfinder samples from.
+    pub fn space(&self) -> &S {
+        &self.space
+    }
+
+    /// Returns the amount of nodes expanded during the last calculation.
+    pub fn expanded(&self) -> usize {
+        self.expanded
+    }
+
+    /// Drops every node the last calculation left behind. Calculations start with this anyway, so
+    /// it's only needed to free their memory early.
     pub fn reset(&mut self) {
         self.open_set.clear();
-        self.closed_set.clear();
+        self.nodes.clear();
+        self.node_map.clear();
+        self.interactions.clear();
+        self.best_node = None;
+        self.expanded = 0;
+    }
+}
+
+/// A flag shared between a running calculation and whoever may want to stop it. Cancellation is
+/// cooperative: the calculator checks it between iterations and returns a cancelled result.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    /// Asks every calculation using this token to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
     }
 }
 
 /// Represents a point in graph space. It is a requirement that any position in graph space be
 /// related to any other graph position in an N-dimensional space.
 pub trait GraphPosition: Copy + Hash + Eq + Debug + Add<Output = Self> {
+    /// Packs the position into a single `i64`, used as its key when looking up nodes. Must be
+    /// unique for every position the pathfinder can reach, so implementations with a limited range
+    /// have to say what it is.
+    fn pack(&self) -> i64;
     /// Calculates the distance from one `NodePosition` position to another.
     fn distance(a: &Self, b: &Self) -> f64;
     /// Calculates the distance from `self` to another `NodePosition`.
@@ -0,0 +1,271 @@
+use crate::binding::jni::JNICompatible;
+use crate::binding::util::{enum_constant, enum_name};
+use crate::pathing::chunk::{BlockState, AIR};
+use eyre::{eyre, Result};
+use jni::objects::JObject;
+use jni::JNIEnv;
+use serde::{Deserialize, Serialize};
+use serde_json::from_str;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+/// How an entity can move through (or on) a block.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockClass {
+    /// Nothing in the way.
+    Air,
+    /// A full block that can be stood on, but not passed through.
+    Solid,
+    /// A liquid that can be swum through.
+    Liquid(Fluid),
+    /// Something that can be climbed, like ladders or vines.
+    Climbable(Climb),
+    /// Passable, but hurts the entity (fire, cactus, sweet berry bushes...).
+    Damaging,
+    /// A block that doesn't fill its whole space, like slabs and stairs.
+    Partial,
+    /// A door, trapdoor or fence gate. Passable while open, see the `open` state property.
+    Openable(Openable)
+}
+
+/// The kinds of liquid a `BlockClass::Liquid` can be.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Fluid {
+    Water,
+    Lava
+}
+
+/// The kinds of thing a `BlockClass::Climbable` can be.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Climb {
+    Ladder,
+    Vine,
+    /// Unlike the others, scaffolding can also be stood on.
+    Scaffolding
+}
+
+/// The kinds of thing a `BlockClass::Openable` can be.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Openable {
+    Door,
+    /// Only opened by redstone, never by hand.
+    IronDoor,
+    /// Can be stood on while closed.
+    Trapdoor,
+    /// Only opened by redstone, never by hand.
+    IronTrapdoor,
+    FenceGate
+}
+
+impl Openable {
+    /// Returns whether the entity can open this itself.
+    pub fn by_hand(&self) -> bool {
+        !matches!(self, Openable::IronDoor | Openable::IronTrapdoor)
+    }
+}
+
+/// Kinds of tools, as far as mining goes.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolKind {
+    Pickaxe,
+    Axe,
+    Shovel,
+    Hoe,
+    Shears,
+    Sword
+}
+
+impl ToolKind {
+    pub const ALL: [ToolKind; 6] = [
+        ToolKind::Pickaxe, ToolKind::Axe, ToolKind::Shovel, ToolKind::Hoe, ToolKind::Shears, ToolKind::Sword
+    ];
+
+    /// Name of the matching constant in the Java enum.
+    const fn java_name(&self) -> &'static str {
+        match self {
+            ToolKind::Pickaxe => "PICKAXE",
+            ToolKind::Axe => "AXE",
+            ToolKind::Shovel => "SHOVEL",
+            ToolKind::Hoe => "HOE",
+            ToolKind::Shears => "SHEARS",
+            ToolKind::Sword => "SWORD"
+        }
+    }
+}
+
+impl <'local> JNICompatible<'local> for ToolKind {
+    const CLASS: &'static str = "com/genericbadname/s4mc/pathing/ToolKind";
+
+    fn to_jni(&self, env: &mut JNIEnv<'local>) -> Result<JObject<'local>> {
+        enum_constant(env, Self::CLASS, self.java_name())
+    }
+
+    fn from_jni(env: &mut JNIEnv<'local>, object: JObject<'local>) -> Result<Self>
+    where
+        Self: Sized
+    {
+        let name = enum_name(env, &object)?;
+
+        Self::ALL.into_iter()
+            .find(|kind| kind.java_name() == name)
+            .ok_or_else(|| eyre!("Unknown tool kind {}", name))
+    }
+}
+
+/// Everything the pathfinder knows about a single block state.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BlockInfo {
+    /// Namespaced ID of the block, e.g. `minecraft:stone`.
+    pub name: String,
+    /// Passability class of the block.
+    pub class: BlockClass,
+    /// State properties, e.g. `waterlogged = true`.
+    #[serde(default)]
+    pub properties: BTreeMap<String, String>,
+    /// Multiplier for the damage taken when falling onto this block, e.g. `0.2` for hay bales.
+    #[serde(default = "BlockInfo::default_fall_damage")]
+    pub fall_damage: f64,
+    /// How long the block takes to mine, or `Option::None` if it can't be broken at all.
+    #[serde(default)]
+    pub hardness: Option<f64>,
+    /// Tool that mines the block faster.
+    #[serde(default)]
+    pub tool: Option<ToolKind>,
+    /// Whether the block only drops with the right tool, which also slows mining it without one.
+    #[serde(default)]
+    pub requires_tool: bool,
+    /// Whether the block falls when there's nothing under it, like sand and gravel.
+    #[serde(default)]
+    pub gravity: bool
+}
+
+impl BlockInfo {
+    pub fn new(name: &str, class: BlockClass) -> BlockInfo {
+        BlockInfo {
+            name: name.to_string(),
+            class,
+            properties: BTreeMap::new(),
+            fall_damage: Self::default_fall_damage(),
+            hardness: None,
+            tool: None,
+            requires_tool: false,
+            gravity: false
+        }
+    }
+
+    /// Sets the fall damage multiplier of this block.
+    pub fn with_fall_damage(mut self, fall_damage: f64) -> BlockInfo {
+        self.fall_damage = fall_damage;
+        self
+    }
+
+    /// Sets how long the block takes to mine, and which tool mines it faster.
+    pub fn with_hardness(mut self, hardness: f64, tool: Option<ToolKind>) -> BlockInfo {
+        self.hardness = Some(hardness);
+        self.tool = tool;
+        self
+    }
+
+    /// Gets the value of a state property, if present.
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.properties.get(key).map(|v| v.as_str())
+    }
+
+    fn default_fall_damage() -> f64 {
+        1.0
+    }
+}
+
+/// A single entry of a JSON block table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BlockEntry {
+    id: BlockState,
+    #[serde(flatten)]
+    info: BlockInfo
+}
+
+/// Maps block states to what the pathfinder should make of them. Block state IDs are assigned by
+/// the game, so the table is supplied from the outside (usually as JSON), which also lets modded
+/// blocks be classified without recompiling.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockRegistry {
+    /// Block info, indexed by block state.
+    blocks: Vec<Option<BlockInfo>>
+}
+
+impl BlockRegistry {
+    /// Creates a registry that only knows about `AIR`.
+    pub fn new() -> BlockRegistry {
+        let mut registry = BlockRegistry { blocks: Vec::new() };
+        registry.register(AIR, BlockInfo::new("minecraft:air", BlockClass::Air));
+        registry
+    }
+
+    /// Registers a block state, replacing (and returning) any existing registration.
+    pub fn register(&mut self, state: BlockState, info: BlockInfo) -> Option<BlockInfo> {
+        let idx = state as usize;
+        if idx >= self.blocks.len() {
+            self.blocks.resize(idx + 1, None);
+        }
+
+        self.blocks[idx].replace(info)
+    }
+
+    /// Gets the info registered for a block state.
+    pub fn get(&self, state: BlockState) -> Option<&BlockInfo> {
+        self.blocks.get(state as usize)?.as_ref()
+    }
+
+    /// Gets the class registered for a block state.
+    pub fn class_of(&self, state: BlockState) -> Option<BlockClass> {
+        self.get(state).map(|info| info.class)
+    }
+
+    /// Returns the amount of registered block states.
+    pub fn len(&self) -> usize {
+        self.blocks.iter().filter(|b| b.is_some()).count()
+    }
+
+    /// Returns whether nothing at all is registered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Parses a JSON block table on top of the defaults. The table is a list of block states:
+    /// `[{ "id": 1, "name": "minecraft:stone", "class": "solid" }, ...]`
+    pub fn from_json(json: &str) -> Result<BlockRegistry> {
+        let entries: Vec<BlockEntry> = from_str(json)?;
+        let mut registry = BlockRegistry::new();
+        for entry in entries {
+            registry.register(entry.id, entry.info);
+        }
+
+        Ok(registry)
+    }
+
+    /// Reads a JSON block table from a given path.
+    pub fn read_registry(path: &Path) -> Result<BlockRegistry> {
+        if !path.exists() {
+            return Err(eyre!("Path does not exist, tried reading empty block table!"))
+        }
+
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut in_str = String::new();
+        reader.read_to_string(&mut in_str)?;
+
+        Self::from_json(&in_str)
+    }
+}
+
+impl Default for BlockRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,318 @@
+use crate::pathing::math::Vector3i;
+use crate::vec3i;
+use eyre::{eyre, Result};
+
+/// Numeric ID of a block state. These are assigned by the game (or by whatever is feeding the
+/// world), the pathfinder only cares about what the ID maps to.
+pub type BlockState = u16;
+
+/// The block state that every unset voxel defaults to.
+pub const AIR: BlockState = 0;
+/// Side length of a chunk section, in blocks.
+pub const SECTION_SIZE: i32 = 16;
+/// Amount of voxels contained within a single chunk section.
+pub const SECTION_VOLUME: usize = (SECTION_SIZE * SECTION_SIZE * SECTION_SIZE) as usize;
+
+/// Smallest amount of bits used per palette index, matching the vanilla format. Keeps small
+/// palettes from being resized every time a new state shows up.
+const MIN_PALETTE_BITS: u32 = 4;
+/// Largest amount of bits used per palette index. Past this, states are stored directly.
+const MAX_PALETTE_BITS: u32 = 8;
+
+/// A 16x16x16 cube of block states, the same unit Minecraft splits its chunks into vertically.
+/// States are palette-compressed: each voxel stores an index into a per-section palette packed
+/// into as few bits as the palette allows, and sections with only one state store nothing but it.
+#[derive(Debug, Clone)]
+pub struct ChunkSection {
+    storage: SectionStorage
+}
+
+/// The underlying representation of a `ChunkSection`.
+#[derive(Debug, Clone)]
+enum SectionStorage {
+    /// Every voxel in the section is the same state.
+    Single(BlockState),
+    /// Voxels are indices into `palette`, packed into `indices`.
+    Indirect {
+        palette: Vec<BlockState>,
+        indices: PackedArray
+    },
+    /// The palette grew too large to be worth it, so states are stored as-is.
+    Direct(Box<[BlockState]>)
+}
+
+impl ChunkSection {
+    /// Creates a section completely filled with air.
+    pub fn new() -> ChunkSection {
+        Self::filled(AIR)
+    }
+
+    /// Creates a section where every voxel is `state`.
+    pub fn filled(state: BlockState) -> ChunkSection {
+        ChunkSection {
+            storage: SectionStorage::Single(state)
+        }
+    }
+
+    /// Creates a section from a full array of block states in YZX order. Fails if `blocks` does not
+    /// contain exactly `SECTION_VOLUME` entries.
+    pub fn from_blocks(blocks: Vec<BlockState>) -> Result<ChunkSection> {
+        if blocks.len() != SECTION_VOLUME {
+            return Err(eyre!("Expected {} block states for a section, got {}", SECTION_VOLUME, blocks.len()))
+        }
+
+        Ok(Self::pack(&blocks))
+    }
+
+    /// Creates a section from a palette and a full array of indices into it, in YZX order. This is
+    /// how the game sends sections over, so they can be copied in without resolving every block
+    /// first. The palette doesn't have to be minimal, it's rebuilt from the states actually used.
+    /// Fails if there aren't exactly `SECTION_VOLUME` indices, or if any are out of bounds.
+    pub fn from_palette(palette: &[BlockState], indices: &[u16]) -> Result<ChunkSection> {
+        if indices.len() != SECTION_VOLUME {
+            return Err(eyre!("Expected {} palette indices for a section, got {}", SECTION_VOLUME, indices.len()))
+        }
+
+        let blocks = indices.iter()
+            .map(|idx| palette.get(*idx as usize).copied()
+                .ok_or_else(|| eyre!("Palette index {} out of bounds for a palette of {}", idx, palette.len())))
+            .collect::<Result<Vec<BlockState>>>()?;
+        Ok(Self::pack(&blocks))
+    }
+
+    /// Gets the block state at a local position. Each component is wrapped to the section bounds.
+    pub fn get(&self, local: Vector3i) -> BlockState {
+        self.get_idx(Self::index_of(local))
+    }
+
+    /// Sets the block state at a local position, returning the previous state. Each component is
+    /// wrapped to the section bounds.
+    pub fn set(&mut self, local: Vector3i, state: BlockState) -> BlockState {
+        let idx = Self::index_of(local);
+        let previous = self.get_idx(idx);
+        if previous == state { return previous }
+
+        match &mut self.storage {
+            SectionStorage::Single(current) => {
+                // split into a two-entry palette, everything but `idx` pointing at the old state
+                let mut indices = PackedArray::new(MIN_PALETTE_BITS, SECTION_VOLUME);
+                indices.set(idx, 1);
+                self.storage = SectionStorage::Indirect {
+                    palette: vec![*current, state],
+                    indices
+                };
+            }
+            SectionStorage::Indirect { palette, indices } => {
+                let palette_idx = match palette.iter().position(|s| *s == state) {
+                    Some(existing) => existing,
+                    None => {
+                        palette.push(state);
+                        palette.len() - 1
+                    }
+                };
+
+                if palette_idx as u64 > indices.max_value() {
+                    // the palette outgrew the index width, so widen (or give up on the palette)
+                    let bits = indices.bits() + 1;
+                    if bits > MAX_PALETTE_BITS {
+                        let mut blocks = self.unpack();
+                        blocks[idx] = state;
+                        self.storage = SectionStorage::Direct(blocks.into_boxed_slice());
+                    } else {
+                        *indices = indices.resized(bits);
+                        indices.set(idx, palette_idx as u64);
+                    }
+                } else {
+                    indices.set(idx, palette_idx as u64);
+                }
+            }
+            SectionStorage::Direct(blocks) => {
+                blocks[idx] = state;
+            }
+        }
+
+        previous
+    }
+
+    /// Rebuilds the palette from the states actually present, dropping entries that are no longer
+    /// used and collapsing the section into a single value where possible.
+    pub fn compact(&mut self) {
+        if let SectionStorage::Single(_) = self.storage { return }
+        *self = Self::pack(&self.unpack());
+    }
+
+    /// Returns the amount of distinct states in the palette, or `Option::None` if states are
+    /// stored directly.
+    pub fn palette_len(&self) -> Option<usize> {
+        match &self.storage {
+            SectionStorage::Single(_) => Some(1),
+            SectionStorage::Indirect { palette, .. } => Some(palette.len()),
+            SectionStorage::Direct(_) => None
+        }
+    }
+
+    /// Returns the amount of bits used per voxel.
+    pub fn bits_per_entry(&self) -> u32 {
+        match &self.storage {
+            SectionStorage::Single(_) => 0,
+            SectionStorage::Indirect { indices, .. } => indices.bits(),
+            SectionStorage::Direct(_) => BlockState::BITS
+        }
+    }
+
+    /// Returns the approximate amount of heap and inline memory this section occupies, in bytes.
+    pub fn memory_usage(&self) -> usize {
+        let heap = match &self.storage {
+            SectionStorage::Single(_) => 0,
+            SectionStorage::Indirect { palette, indices } =>
+                palette.capacity() * size_of::<BlockState>() + indices.memory_usage(),
+            SectionStorage::Direct(blocks) => blocks.len() * size_of::<BlockState>()
+        };
+
+        size_of::<Self>() + heap
+    }
+
+    /// Returns every block state in YZX order.
+    pub fn unpack(&self) -> Vec<BlockState> {
+        (0..SECTION_VOLUME).map(|idx| self.get_idx(idx)).collect()
+    }
+
+    /// Builds the smallest representation for a full array of block states.
+    fn pack(blocks: &[BlockState]) -> ChunkSection {
+        let mut palette: Vec<BlockState> = Vec::new();
+        for state in blocks.iter() {
+            if !palette.contains(state) {
+                palette.push(*state);
+                // no need to keep counting once the section is going to be stored directly
+                if palette.len() > 1 << MAX_PALETTE_BITS { break }
+            }
+        }
+
+        let storage = match palette.len() {
+            0 => SectionStorage::Single(AIR),
+            1 => SectionStorage::Single(palette[0]),
+            len => {
+                let bits = (usize::BITS - (len - 1).leading_zeros()).max(MIN_PALETTE_BITS);
+                if bits > MAX_PALETTE_BITS {
+                    SectionStorage::Direct(blocks.to_vec().into_boxed_slice())
+                } else {
+                    let mut indices = PackedArray::new(bits, SECTION_VOLUME);
+                    for (idx, state) in blocks.iter().enumerate() {
+                        // the palette was built from these blocks, so the position always exists
+                        let palette_idx = palette.iter().position(|s| s == state).unwrap_or(0);
+                        indices.set(idx, palette_idx as u64);
+                    }
+                    palette.shrink_to_fit();
+                    SectionStorage::Indirect { palette, indices }
+                }
+            }
+        };
+
+        ChunkSection { storage }
+    }
+
+    /// Gets the block state at an index into the section.
+    fn get_idx(&self, idx: usize) -> BlockState {
+        match &self.storage {
+            SectionStorage::Single(state) => *state,
+            SectionStorage::Indirect { palette, indices } =>
+                palette.get(indices.get(idx) as usize).copied().unwrap_or(AIR),
+            SectionStorage::Direct(blocks) => blocks[idx]
+        }
+    }
+
+    /// Converts a local position to an index into the section.
+    fn index_of(local: Vector3i) -> usize {
+        let mask = SECTION_SIZE - 1;
+        (((local.y & mask) << 8) | ((local.z & mask) << 4) | (local.x & mask)) as usize
+    }
+}
+
+impl PartialEq for ChunkSection {
+    fn eq(&self, other: &Self) -> bool {
+        // representations can differ for the same contents, so compare voxel by voxel
+        (0..SECTION_VOLUME).all(|idx| self.get_idx(idx) == other.get_idx(idx))
+    }
+}
+
+impl Eq for ChunkSection {}
+
+impl Default for ChunkSection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns the coordinates of the section containing the block at `pos`.
+pub const fn section_pos_of(pos: Vector3i) -> Vector3i {
+    vec3i!(pos.x >> 4, pos.y >> 4, pos.z >> 4)
+}
+
+/// Returns the position of `pos` relative to the origin of its section.
+pub const fn local_pos_of(pos: Vector3i) -> Vector3i {
+    let mask = SECTION_SIZE - 1;
+    vec3i!(pos.x & mask, pos.y & mask, pos.z & mask)
+}
+
+/// A fixed-length array of unsigned integers packed into `u64` words. Like the vanilla format,
+/// entries never span two words, so some bits at the top of each word may go unused.
+#[derive(Debug, Clone)]
+struct PackedArray {
+    /// Backing words.
+    words: Box<[u64]>,
+    /// Bits used by each entry.
+    bits: u32,
+    /// Amount of entries that fit in a single word.
+    per_word: usize,
+    /// Amount of entries in the array.
+    len: usize
+}
+
+impl PackedArray {
+    /// Creates a zeroed array of `len` entries, each `bits` wide.
+    fn new(bits: u32, len: usize) -> PackedArray {
+        let per_word = (u64::BITS / bits) as usize;
+        PackedArray {
+            words: vec![0; len.div_ceil(per_word)].into_boxed_slice(),
+            bits,
+            per_word,
+            len
+        }
+    }
+
+    /// Returns the bits used by each entry.
+    fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// Returns the largest value an entry can hold.
+    fn max_value(&self) -> u64 {
+        (1 << self.bits) - 1
+    }
+
+    fn get(&self, idx: usize) -> u64 {
+        let shift = (idx % self.per_word) as u32 * self.bits;
+        (self.words[idx / self.per_word] >> shift) & self.max_value()
+    }
+
+    fn set(&mut self, idx: usize, value: u64) {
+        let shift = (idx % self.per_word) as u32 * self.bits;
+        let mask = self.max_value();
+        let word = &mut self.words[idx / self.per_word];
+        *word = (*word & !(mask << shift)) | ((value & mask) << shift);
+    }
+
+    /// Copies every entry into a new array with a different width.
+    fn resized(&self, bits: u32) -> PackedArray {
+        let mut out = PackedArray::new(bits, self.len);
+        for idx in 0..self.len {
+            out.set(idx, self.get(idx));
+        }
+        out
+    }
+
+    /// Returns the amount of heap memory used by the words, in bytes.
+    fn memory_usage(&self) -> usize {
+        self.words.len() * size_of::<u64>()
+    }
+}
@@ -1,13 +1,15 @@
 use crate::binding::jni::JNICompatible;
-use eyre::{eyre, OptionExt, Result};
-use jni::objects::{JObject, JValueGen};
+use crate::binding::util::{enum_constant, enum_name};
+use eyre::{eyre, Result};
+use jni::objects::JObject;
 use jni::JNIEnv;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::fmt::{Display, Formatter};
-use crate::pathing::action::{Moveset2D, SpatialAction};
+use crate::pathing::action::{Interaction, SpatialAction};
+use crate::pathing::agent::Agent;
 use crate::pathing::algorithm::GraphPosition;
-use crate::pathing::math::Vector2i;
+use crate::pathing::block::{BlockClass, Fluid};
 
 /// A node within the A* graph.
 #[derive(Debug, Copy, Clone)]
@@ -19,21 +21,28 @@ pub struct Node<P> where P: GraphPosition
     pub h_cost: f64,
     /// The node's position in space and how the pathfinder moved to it.
     pub action: SpatialAction<P>,
-    /// Parent of this node. If `Option::None`, this is considered the root node.
-    pub parent: Option<SpatialAction<P>>,
+    /// Arena index of this node's parent. If `Option::None`, this is considered the root node.
+    pub parent: Option<u32>,
     /// Open-set heap index. If `Option::None`, this `Node` is Closed.
-    pub heap_idx: Option<usize>
+    pub heap_idx: Option<usize>,
+    /// What the entity has left after following the path up to this node.
+    pub state: PathState,
+    /// Index of what has to be done to the world to move here from the parent, in the calculator's
+    /// interaction arena. If `Option::None`, the move is just walking.
+    pub interactions: Option<u32>
 }
 impl <P> Node<P> where P: GraphPosition
 {
     /// Constructs the root `Node`, which is has a root `SpatialAction` and a cost of 0.
-    pub fn start_node(start: P, end: &P) -> Self<> {
+    pub fn start_node(start: P, h_cost: f64, state: PathState) -> Self<> {
         Self {
             g_cost: 0.0,
-            h_cost: start.distance_to(end),
+            h_cost,
             parent: None,
             action: SpatialAction::new_root(start),
-            heap_idx: None
+            heap_idx: None,
+            state,
+            interactions: None
         }
     }
 
@@ -42,39 +51,29 @@ impl <P> Node<P> where P: GraphPosition
         self.g_cost + self.h_cost
     }
 
+    /// Returns whether this `Node` is closer to the goal than `other`, going by the heuristic.
+    /// Cheaper nodes win ties.
+    pub fn closer_than(&self, other: &Node<P>) -> bool {
+        self.h_cost < other.h_cost || (self.h_cost == other.h_cost && self.g_cost < other.g_cost)
+    }
+
     /// Returns whether this `Node` is Open, meaning it has a valid heap index.
     pub fn is_open(&self) -> bool {
         self.heap_idx.is_some()
     }
 }
 
-impl <'a, P> PartialEq<Self> for Node<P> where P: GraphPosition
+impl <P> PartialEq<Self> for Node<P> where P: GraphPosition
 {
     fn eq(&self, other: &Self) -> bool {
         self.f_cost().eq(&other.f_cost())
     }
 }
 
-impl <'a, P> PartialOrd for Node<P> where P: GraphPosition
+impl <P> PartialOrd for Node<P> where P: GraphPosition
 {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.f_cost().partial_cmp(&other.f_cost())
-    }
-
-    fn lt(&self, other: &Self) -> bool {
-        self.f_cost().lt(&other.f_cost())
-    }
-
-    fn le(&self, other: &Self) -> bool {
-        self.f_cost().le(&other.f_cost())
-    }
-
-    fn gt(&self, other: &Self) -> bool {
-        self.f_cost().gt(&other.f_cost())
-    }
-
-    fn ge(&self, other: &Self) -> bool {
-        self.f_cost().ge(&other.f_cost())
+        Some(self.cmp(other))
     }
 }
 
@@ -85,43 +84,147 @@ impl <P> Ord for Node<P> where P: GraphPosition {
     }
 }
 
+/// Resources of the entity that get used up along a path. This is tracked per node, but isn't part
+/// of what identifies a node: a position keeps the state of the cheapest path to it, even if a
+/// pricier one would have had more left over.
+#[derive(Debug, Copy, Clone, Default, Hash, Eq, PartialEq)]
+pub struct PathState {
+    /// Throwaway blocks left to place.
+    pub blocks: u32,
+    /// Ticks of air left before drowning. Refilled whenever the entity's head is out of the water.
+    pub air: u32,
+    /// Whether the entity stands on a block placed by the move that got it here, which isn't part
+    /// of the world yet.
+    pub on_placed: bool
+}
+
+impl PathState {
+    /// Returns the state an agent starts out with.
+    pub fn new(agent: &Agent) -> PathState {
+        PathState {
+            blocks: agent.throwaway_blocks,
+            air: agent.air_supply,
+            on_placed: false
+        }
+    }
+
+    /// Returns the state after placing a throwaway block to stand on.
+    pub fn place_block(&self) -> PathState {
+        PathState {
+            blocks: self.blocks.saturating_sub(1),
+            on_placed: true,
+            ..*self
+        }
+    }
+}
+
 /// A node on the path to a given destination. Does not contain unnecessary cost data like with
 /// `Node`, only movement-related information.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, JNICompatible)]
+#[jni(class = "com/genericbadname/s4mc/pathing/PathNode")]
 pub struct PathNode<P> where P: GraphPosition
 {
-    pub action: SpatialAction<P>
+    pub action: SpatialAction<P>,
+    /// What has to be done to the world to get to this node, in order.
+    pub interactions: Vec<Interaction<P>>
 }
 
 impl <P> PathNode<P> where P: GraphPosition {
     pub fn new(action: SpatialAction<P>) -> Self {
-        Self { action }
+        Self { action, interactions: Vec::new() }
+    }
+
+    pub fn with_interactions(action: SpatialAction<P>, interactions: Vec<Interaction<P>>) -> Self {
+        Self { action, interactions }
+    }
+
+    /// Returns whether reaching this node takes more than just moving.
+    pub fn requires_interaction(&self) -> bool {
+        !self.interactions.is_empty()
     }
 }
 
-impl <'local, P> JNICompatible<'local> for PathNode<P> where P: GraphPosition + JNICompatible<'local> {
-    const CLASS: &'static str = "com/genericbadname/s4mc/pathing/PathNode";
+/// How a path calculation ended.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum PathStatus {
+    /// The path reaches the goal.
+    Complete,
+    /// The pathfinder ran out of time, or the goal can't be reached. The path leads to the node that
+    /// got closest to the goal, so the entity can start moving and request a continuation from
+    /// there.
+    Partial,
+    /// No path could be found, the path is empty.
+    Failed,
+    /// The calculation was cancelled before it finished, the path is empty.
+    Cancelled
+}
+
+impl PathStatus {
+    /// Name of the matching constant in the Java enum.
+    const fn java_name(&self) -> &'static str {
+        match self {
+            PathStatus::Complete => "COMPLETE",
+            PathStatus::Partial => "PARTIAL",
+            PathStatus::Failed => "FAILED",
+            PathStatus::Cancelled => "CANCELLED"
+        }
+    }
+}
+
+impl <'local> JNICompatible<'local> for PathStatus {
+    const CLASS: &'static str = "com/genericbadname/s4mc/pathing/PathStatus";
 
     fn to_jni(&self, env: &mut JNIEnv<'local>) -> Result<JObject<'local>> {
-        let pathnode_class = env.find_class(Self::CLASS)?;
-        let pos = self.action.to_jni(env)?;
-        Ok(env.new_object(pathnode_class, "(Lcom/genericbadname/s4mc/math/Vector3i;)V", &[
-            JValueGen::Object(&pos),
-        ])?)
+        enum_constant(env, Self::CLASS, self.java_name())
     }
 
     fn from_jni(env: &mut JNIEnv<'local>, object: JObject<'local>) -> Result<Self>
     where
         Self: Sized
     {
-        // TODO: update this!!
-        let pos = env.call_method(
-            &object, "pos", "()Lgenericbadname/s4mc/math/Vector3i;", &[]
-        )?.l()?;
+        let name = enum_name(env, &object)?;
+
+        [PathStatus::Complete, PathStatus::Partial, PathStatus::Failed, PathStatus::Cancelled].into_iter()
+            .find(|status| status.java_name() == name)
+            .ok_or_else(|| eyre!("Unknown path status {}", name))
+    }
+}
+
+/// The outcome of a path calculation.
+#[derive(Debug, Clone, PartialEq, Eq, JNICompatible)]
+#[jni(class = "com/genericbadname/s4mc/pathing/PathResult")]
+pub struct PathResult<P> where P: GraphPosition {
+    /// How the calculation ended.
+    pub status: PathStatus,
+    /// The calculated path, starting at the start position. Empty if the calculation failed.
+    pub path: Vec<PathNode<P>>
+}
+
+impl <P> PathResult<P> where P: GraphPosition {
+    pub fn complete(path: Vec<PathNode<P>>) -> Self {
+        Self { status: PathStatus::Complete, path }
+    }
+
+    pub fn partial(path: Vec<PathNode<P>>) -> Self {
+        Self { status: PathStatus::Partial, path }
+    }
+
+    pub fn failed() -> Self {
+        Self { status: PathStatus::Failed, path: vec![] }
+    }
+
+    pub fn cancelled() -> Self {
+        Self { status: PathStatus::Cancelled, path: vec![] }
+    }
 
-        Ok(Self {
-            action: SpatialAction::from_jni(env, pos)?
-        })
+    /// Returns whether the path reaches the goal.
+    pub fn is_complete(&self) -> bool {
+        self.status == PathStatus::Complete
+    }
+
+    /// Returns the position the path ends at, if there is one.
+    pub fn end(&self) -> Option<P> {
+        self.path.last().map(|n| n.action.pos)
     }
 }
 
@@ -129,11 +232,12 @@ impl <'local, P> JNICompatible<'local> for PathNode<P> where P: GraphPosition +
 /// All methods take an `i32`, these are just explicitly defined for convenience.
 #[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct HazardMultiplier {
-    /// Unknown. Could be any type of object.
+    /// Unknown. Could be any type of object, so it's `IMPASSABLE` unless given a multiplier.
     pub unknown: u32,
     /// Non-solid obstacle that the entity can pass through.
     pub non_solid: u32,
-    /// Solid obstacle that the entity can't pass through.
+    /// Partly solid obstacle that the entity can still move into, like a slab or stairs. Full
+    /// blocks are never passable, whatever this is set to.
     pub solid: u32,
     /// Dangerous obstacle that should be avoided.
     pub dangerous: u32
@@ -143,7 +247,7 @@ impl HazardMultiplier {
     /// Creates an instance with the default values.
     pub fn new() -> HazardMultiplier {
         HazardMultiplier {
-            unknown: 10,
+            unknown: Self::IMPASSABLE,
             non_solid: 21,
             solid: 10,
             dangerous: 50
@@ -151,18 +255,60 @@ impl HazardMultiplier {
     }
 }
 
+impl HazardMultiplier {
+    /// Hazard values are stored in tenths, so a value of `10` leaves costs untouched.
+    const SCALE: f64 = 10.0;
+    /// Makes blocks impassable instead of multiplying their cost. Moving into a block for free
+    /// isn't something any block should allow, so `0` is free to mean this.
+    pub const IMPASSABLE: u32 = 0;
+
+    /// Returns the cost multiplier for moving into a block of `class`, or `Option::None` if the
+    /// block can't be moved into at all. Unregistered blocks are passed as `Option::None`, and are
+    /// impassable unless `unknown` is set.
+    pub fn multiplier_for(&self, class: Option<BlockClass>) -> Option<f64> {
+        let value = match class {
+            None => self.unknown,
+            // whether it's actually open is up to the space, as that's part of the block's state
+            Some(BlockClass::Air | BlockClass::Openable(_)) => return Some(1.0),
+            Some(BlockClass::Solid) => return None,
+            Some(BlockClass::Partial) => self.solid,
+            Some(BlockClass::Liquid(Fluid::Water) | BlockClass::Climbable(_)) => self.non_solid,
+            Some(BlockClass::Liquid(Fluid::Lava) | BlockClass::Damaging) => self.dangerous
+        };
+
+        if value == Self::IMPASSABLE { return None }
+        Some(value as f64 / Self::SCALE)
+    }
+}
+
+impl Default for HazardMultiplier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// An Open Set backed by a Binary Heap. Differs from `std::collections::BinaryHeap` in that it
 /// implements decrease-key operations necessary for Dijkstra's algorithm to not have terrible
 /// space complexity. This is an "implicit heap," that is, parent-child relationships are not
 /// stored through pointers, only indices into a `Vec`.
 ///
+/// The heap doesn't own any `Node`s, it stores indices into a node arena (a slice of `Node`s) that
+/// has to be passed to every operation. Each `Node`'s `heap_idx` is kept up to date as the heap
+/// shifts around.
+///
 /// This implementation handles events in which the keys on a `Node` are somehow mutated when they
 /// shouldn't, or are mismatched from their real values. However, `Nodes` that modify themselves
 /// through interior mutability patterns to change their cost/key are considered undefined
 /// behavior.
-pub struct BinaryHeapOpenSet<P> where P: GraphPosition {
-    /// Underlying data structure of the set.
-    data: Vec<Node<P>>
+pub struct BinaryHeapOpenSet {
+    /// Underlying data structure of the set. Holds indices into the node arena.
+    data: Vec<u32>
+}
+
+impl Default for BinaryHeapOpenSet {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// Simple struct for comparing keys & values in an Open Set.
@@ -180,28 +326,13 @@ impl SetEntry {
     }
 }
 
-impl <P> From<Node<P>> for SetEntry where P: GraphPosition {
-    fn from(value: Node<P>) -> Self {
-        Self::from(&value)
-    }
-}
-
-impl <P> From<&Node<P>> for SetEntry where P: GraphPosition {
-    fn from(value: &Node<P>) -> Self {
-        Self {
-            idx: value.heap_idx.unwrap(),
-            cost: value.f_cost()
-        }
-    }
-}
-
 impl Display for SetEntry {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "(idx: {}, cost: {})", self.idx, self.cost)
     }
 }
 
-impl <P> BinaryHeapOpenSet<P> where P: GraphPosition {
+impl BinaryHeapOpenSet {
     pub fn new() -> Self {
         Self {
             data: Vec::with_capacity(1024)
@@ -218,61 +349,59 @@ impl <P> BinaryHeapOpenSet<P> where P: GraphPosition {
         (idx << 1) + 1
     }
 
-    /// Returns a `SetEntry` for the specified index, or an `Err` if none exists.
-    fn entry_for(&self, idx: usize) -> Result<SetEntry> {
-        let se = SetEntry::from(self.data.get(idx)
-            .ok_or_else(|| eyre!("Couldn't get a node (index {})", idx))?);
-        Ok(se)
-    }
-
-    /// Insert a new `Node` into the Open Set, setting `node`'s `heap_idx`.
-    pub fn insert(&mut self, node: &mut Node<P>) -> Result<()> {
-        node.heap_idx = Some(self.data.len());
-        self.data.push(*node);
-        self.sift_up(node)
+    /// Returns a `SetEntry` for the specified heap index, or an `Err` if none exists.
+    fn entry_for<P>(&self, nodes: &[Node<P>], idx: usize) -> Result<SetEntry> where P: GraphPosition {
+        let node_idx = *self.data.get(idx)
+            .ok_or_else(|| eyre!("Couldn't get a node (index {})", idx))?;
+        let node = nodes.get(node_idx as usize)
+            .ok_or_else(|| eyre!("Heap pointed outside of the node arena (node {})", node_idx))?;
+        Ok(SetEntry::new(idx, node.f_cost()))
     }
 
-    pub fn insert_direct(&mut self, mut node: Node<P>) -> Result<()> {
+    /// Insert the `Node` at `node_idx` of the arena into the Open Set, setting its `heap_idx`.
+    pub fn insert<P>(&mut self, nodes: &mut [Node<P>], node_idx: u32) -> Result<()> where P: GraphPosition {
+        let node = nodes.get_mut(node_idx as usize)
+            .ok_or_else(|| eyre!("Tried to insert a node outside of the node arena (node {})", node_idx))?;
         node.heap_idx = Some(self.data.len());
-        self.data.push(node);
-        self.sift_up(&mut node)
+        self.data.push(node_idx);
+        self.sift_up(nodes, node_idx)
     }
 
-    /// Sifts the set from `node` until it is leveled with the rest of the heap.
-    /// Runs a decrease-key operation until fully leveled.
-    pub fn sift_up(&mut self, to_update: &mut Node<P>) -> Result<()> {
+    /// Sifts the set from the `Node` at `node_idx` of the arena until it is leveled with the rest of
+    /// the heap. Runs a decrease-key operation until fully leveled.
+    pub fn sift_up<P>(&mut self, nodes: &mut [Node<P>], node_idx: u32) -> Result<()> where P: GraphPosition {
+        let to_update = nodes.get(node_idx as usize)
+            .ok_or_else(|| eyre!("Tried to sift a node outside of the node arena (node {})", node_idx))?;
         // do not operate on Closed nodes!!
-        if to_update.heap_idx.is_none() { return Err(eyre!("Tried to sift a Closed node")) }
-        if to_update.heap_idx.unwrap() == 0 { return Ok(()) }
+        let Some(heap_idx) = to_update.heap_idx else { return Err(eyre!("Tried to sift a Closed node")) };
+        if heap_idx == 0 { return Ok(()) }
 
-        let mut updating = SetEntry::new(to_update.heap_idx.unwrap(), to_update.f_cost());
-        let mut parent = self.entry_for(Self::parent_of(updating.idx))?;
+        let mut updating = SetEntry::new(heap_idx, to_update.f_cost());
+        let mut parent = self.entry_for(nodes, Self::parent_of(updating.idx))?;
 
         // sift until the node reaches the correct position
         while updating.idx > 0 && parent.cost > updating.cost {
             // swap around
-            self.swap_idx(updating.idx, parent.idx)?;
+            self.swap_idx(nodes, updating.idx, parent.idx)?;
             // and then update our working values
             updating.idx = parent.idx;
-            parent = self.entry_for(Self::parent_of(updating.idx))?;
+            parent = self.entry_for(nodes, Self::parent_of(updating.idx))?;
         }
 
-        to_update.heap_idx = Some(updating.idx);
-
         Ok(())
     }
 
-    fn swap_idx(&mut self, a: usize, b: usize) -> Result<()> {
+    fn swap_idx<P>(&mut self, nodes: &mut [Node<P>], a: usize, b: usize) -> Result<()> where P: GraphPosition {
         let last_idx = self.data.len() - 1;
         if a > last_idx || b > last_idx { return Err(eyre!("Tried to swap an out of bounds index")) }
 
         self.data.swap(a, b);
-        self.data.get_mut(a)
-            .ok_or_else(|| eyre!("Couldn't get node (index {}) to update its heap index", a))?
-            .heap_idx = Some(a);
-        self.data.get_mut(b)
-            .ok_or_else(|| eyre!("Couldn't get node (index {}) to update its heap index", b))?
-            .heap_idx = Some(b);
+        for idx in [a, b] {
+            let node_idx = self.data[idx];
+            nodes.get_mut(node_idx as usize)
+                .ok_or_else(|| eyre!("Couldn't get node (index {}) to update its heap index", idx))?
+                .heap_idx = Some(idx);
+        }
         Ok(())
     }
 
@@ -281,39 +410,40 @@ impl <P> BinaryHeapOpenSet<P> where P: GraphPosition {
         self.data.is_empty()
     }
 
-    /// Removes the lowest cost node from the set. Returns an `Ok(Node<P>)` with the `Node` if it
-    /// was removed and the set was updated successfully.
-    pub fn pop(&mut self) -> Option<Node<P>> {
-        if self.is_empty() { return None }
-        let lowest = self.swap_remove_preserve()?;
-
-        if self.data.len() == 0 { return Some(lowest) }
+    /// Returns the amount of `Node`s in the Open Set.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
 
-        self.data.get_mut(0)?.heap_idx = Some(0);
+    /// Removes the lowest cost node from the set. Returns `Option::Some` with the arena index of
+    /// the `Node` if it was removed and the set was updated successfully.
+    pub fn pop<P>(&mut self, nodes: &mut [Node<P>]) -> Option<u32> where P: GraphPosition {
+        if self.is_empty() { return None }
+        let lowest = self.swap_remove_preserve(nodes)?;
 
         // if there's no more nodes, no need to sift any further
-        if self.data.len() == 1 { return Some(lowest) }
+        if self.data.len() <= 1 { return Some(lowest) }
 
         // otherwise, keep trying to sift down
-        self.sift_down().ok()?;
+        self.sift_down(nodes).ok()?;
         Some(lowest)
     }
 
     /// Sifts the heap from the top down, readjusting from the root `Node` until the heap
     /// is leveled out.
-    fn sift_down(&mut self) -> Result<()> {
+    fn sift_down<P>(&mut self, nodes: &mut [Node<P>]) -> Result<()> where P: GraphPosition {
         let size = self.data.len();
         // parent, originally the topmost node in the tree
-        let mut parent = self.entry_for(0)?;
+        let mut parent = self.entry_for(nodes, 0)?;
         // the left child by default
         let mut child = SetEntry::new(1, 0.0);
 
         while child.idx < size {
-            child.cost = self.entry_for(child.idx)?.cost;
+            child.cost = self.entry_for(nodes, child.idx)?.cost;
             // get which child (left or right) is better to sift them up
             if child.idx + 1 < size {
                 let rc_idx = child.idx + 1;
-                let rc_cost = self.entry_for(rc_idx)?.cost;
+                let rc_cost = self.entry_for(nodes, rc_idx)?.cost;
                 if child.cost > rc_cost {
                     child.idx += 1;
                     child.cost = rc_cost;
@@ -322,7 +452,7 @@ impl <P> BinaryHeapOpenSet<P> where P: GraphPosition {
             // don't swap if the heap is level now
             if parent.cost <= child.cost { break }
             // otherwise, update indices
-            self.swap_idx(parent.idx, child.idx)?;
+            self.swap_idx(nodes, parent.idx, child.idx)?;
             parent.idx = child.idx;
             child.idx = Self::child_of(parent.idx);
         }
@@ -330,40 +460,140 @@ impl <P> BinaryHeapOpenSet<P> where P: GraphPosition {
         Ok(())
     }
 
-    /// Clears the set, completely emptying its contents.
+    /// Clears the set, completely emptying its contents. `Node`s are left as they are, so this
+    /// should be paired with clearing the arena.
     pub fn clear(&mut self) {
         self.data.clear();
     }
 
-    /// Gets a reference to a `Node` at the specified index. Returns `Option::None` if that index
-    /// has no associated value.
-    pub fn get(&self, idx: usize) -> Option<&Node<P>> {
-        self.data.get(idx)
+    /// Gets the arena index of the `Node` at the specified heap index. Returns `Option::None` if
+    /// that index has no associated value.
+    pub fn get(&self, idx: usize) -> Option<u32> {
+        self.data.get(idx).copied()
     }
 
     /// Returns the list of costs within the heap.
-    pub fn cost_order(&self) -> Vec<f64> {
-        self.data.iter().map(|n| n.f_cost()).collect()
+    pub fn cost_order<P>(&self, nodes: &[Node<P>]) -> Vec<f64> where P: GraphPosition {
+        self.data.iter().map(|idx| nodes[*idx as usize].f_cost()).collect()
     }
-    
-    /// Returns the complete data within the set.
-    pub fn clone_data(&self) -> Vec<Node<P>> {
-        self.data.clone()
+
+    /// Returns the arena indices within the set, in heap order.
+    pub fn indices(&self) -> &[u32] {
+        &self.data
     }
 
     /// Swaps the first element with the last and removes it while still preserving data order.
     /// Returns the removed element or `None` if the `vec` was empty.
-    fn swap_remove_preserve(&mut self) -> Option<Node<P>> {
+    fn swap_remove_preserve<P>(&mut self, nodes: &mut [Node<P>]) -> Option<u32> where P: GraphPosition {
         if self.data.is_empty() { return None }
 
         // move last node to root
         let last_idx = self.data.len() - 1;
         self.data.swap(0, last_idx);
-        self.data.get_mut(0)?.heap_idx = Some(0);
         // remove (former) root
-        let mut removing = self.data.remove(last_idx);
-        removing.heap_idx = None;
+        let removing = self.data.pop()?;
+        nodes.get_mut(removing as usize)?.heap_idx = None;
+        if let Some(root) = self.data.first() {
+            nodes.get_mut(*root as usize)?.heap_idx = Some(0);
+        }
 
         Some(removing)
     }
-}
\ No newline at end of file
+}
+
+/// Value marking an empty slot in a `NodeIndexMap`.
+const EMPTY_SLOT: u32 = u32::MAX;
+
+/// An open-addressing hash map from packed positions (see `GraphPosition::pack`) to indices into a
+/// node arena. Uses linear probing over power-of-two sized tables, which is a lot cheaper than a
+/// general purpose map for the lookup-heavy neighbor expansion.
+pub struct NodeIndexMap {
+    /// Packed position of every slot.
+    keys: Vec<i64>,
+    /// Node index of every slot, or `EMPTY_SLOT`.
+    values: Vec<u32>,
+    /// Amount of occupied slots.
+    len: usize
+}
+
+impl NodeIndexMap {
+    /// Largest fraction of slots that may be occupied before the table grows.
+    const LOAD_FACTOR: f64 = 0.75;
+
+    /// Creates a map that can hold at least `capacity` entries before growing.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let slots = ((capacity as f64 / Self::LOAD_FACTOR) as usize).next_power_of_two().max(16);
+        Self {
+            keys: vec![0; slots],
+            values: vec![EMPTY_SLOT; slots],
+            len: 0
+        }
+    }
+
+    /// Scrambles a key so that neighboring positions don't end up in neighboring slots.
+    fn mix(key: i64) -> usize {
+        let h = (key as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        let h = h ^ (h >> 32);
+        (h ^ (h >> 16)) as usize
+    }
+
+    /// Returns the slot holding `key`, or the empty slot where it would go.
+    fn slot_of(&self, key: i64) -> usize {
+        let mask = self.keys.len() - 1;
+        let mut slot = Self::mix(key) & mask;
+        while self.values[slot] != EMPTY_SLOT && self.keys[slot] != key {
+            slot = (slot + 1) & mask;
+        }
+        slot
+    }
+
+    /// Gets the node index stored for `key`.
+    pub fn get(&self, key: i64) -> Option<u32> {
+        let value = self.values[self.slot_of(key)];
+        (value != EMPTY_SLOT).then_some(value)
+    }
+
+    /// Stores a node index for `key`, returning the previous one.
+    pub fn insert(&mut self, key: i64, value: u32) -> Option<u32> {
+        let slot = self.slot_of(key);
+        let previous = self.values[slot];
+        self.keys[slot] = key;
+        self.values[slot] = value;
+        if previous != EMPTY_SLOT { return Some(previous) }
+
+        self.len += 1;
+        if self.len as f64 > self.keys.len() as f64 * Self::LOAD_FACTOR {
+            self.grow();
+        }
+        None
+    }
+
+    /// Doubles the amount of slots, rehashing every entry.
+    fn grow(&mut self) {
+        let keys = std::mem::replace(&mut self.keys, vec![0; self.values.len() * 2]);
+        let values = std::mem::replace(&mut self.values, vec![EMPTY_SLOT; keys.len() * 2]);
+        for (key, value) in keys.into_iter().zip(values) {
+            if value != EMPTY_SLOT {
+                let slot = self.slot_of(key);
+                self.keys[slot] = key;
+                self.values[slot] = value;
+            }
+        }
+    }
+
+    /// Returns the amount of entries in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes every entry, keeping the allocated slots.
+    pub fn clear(&mut self) {
+        self.values.fill(EMPTY_SLOT);
+        self.len = 0;
+    }
+}
@@ -0,0 +1,137 @@
+use crate::pathing::algorithm::GraphPosition;
+use crate::pathing::math::Vector3i;
+
+/// A target for the pathfinder. Rather than a single end position, a `Goal` decides which positions
+/// are acceptable places to stop, and estimates how far away any position is from being one.
+pub trait Goal<P> where P: GraphPosition {
+    /// Returns whether `pos` satisfies this goal.
+    fn is_goal(&self, pos: P) -> bool;
+    /// Estimates the cost from `pos` to the nearest position satisfying this goal. Should never
+    /// overestimate, otherwise the resulting path may not be optimal.
+    fn heuristic(&self, pos: P) -> f64;
+}
+
+/// Reach one exact position.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GoalExact<P>(pub P) where P: GraphPosition;
+
+impl <P> Goal<P> for GoalExact<P> where P: GraphPosition {
+    fn is_goal(&self, pos: P) -> bool {
+        pos == self.0
+    }
+
+    fn heuristic(&self, pos: P) -> f64 {
+        pos.distance_to(&self.0)
+    }
+}
+
+/// Get within `radius` of a position.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GoalNear<P> where P: GraphPosition {
+    pub target: P,
+    pub radius: f64
+}
+
+impl <P> GoalNear<P> where P: GraphPosition {
+    pub fn new(target: P, radius: f64) -> Self {
+        Self { target, radius }
+    }
+}
+
+impl <P> Goal<P> for GoalNear<P> where P: GraphPosition {
+    fn is_goal(&self, pos: P) -> bool {
+        pos.distance_to(&self.target) <= self.radius
+    }
+
+    fn heuristic(&self, pos: P) -> f64 {
+        (pos.distance_to(&self.target) - self.radius).max(0.0)
+    }
+}
+
+/// Reach any of the contained goals.
+pub struct GoalComposite<P> where P: GraphPosition {
+    pub goals: Vec<Box<dyn Goal<P>>>
+}
+
+impl <P> GoalComposite<P> where P: GraphPosition {
+    pub fn new(goals: Vec<Box<dyn Goal<P>>>) -> Self {
+        Self { goals }
+    }
+}
+
+impl <P> Goal<P> for GoalComposite<P> where P: GraphPosition {
+    fn is_goal(&self, pos: P) -> bool {
+        self.goals.iter().any(|g| g.is_goal(pos))
+    }
+
+    fn heuristic(&self, pos: P) -> f64 {
+        // the closest goal is the one that matters
+        self.goals.iter()
+            .map(|g| g.heuristic(pos))
+            .reduce(f64::min)
+            .unwrap_or(0.0)
+    }
+}
+
+/// Get away from the contained goal, until its heuristic is at least `distance`. With an infinite
+/// distance, no position ever satisfies this goal, so the pathfinder runs out of nodes (or time)
+/// and returns a partial path to the position with the best (most negative) heuristic.
+pub struct GoalInverted<P> where P: GraphPosition {
+    pub origin: Box<dyn Goal<P>>,
+    pub distance: f64
+}
+
+impl <P> GoalInverted<P> where P: GraphPosition {
+    pub fn new(origin: Box<dyn Goal<P>>, distance: f64) -> Self {
+        Self { origin, distance }
+    }
+}
+
+impl <P> Goal<P> for GoalInverted<P> where P: GraphPosition {
+    fn is_goal(&self, pos: P) -> bool {
+        self.origin.heuristic(pos) >= self.distance
+    }
+
+    fn heuristic(&self, pos: P) -> f64 {
+        -self.origin.heuristic(pos)
+    }
+}
+
+/// Reach a column, at any height.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GoalXZ {
+    pub x: i32,
+    pub z: i32
+}
+
+impl GoalXZ {
+    pub fn new(x: i32, z: i32) -> Self {
+        Self { x, z }
+    }
+}
+
+impl Goal<Vector3i> for GoalXZ {
+    fn is_goal(&self, pos: Vector3i) -> bool {
+        pos.x == self.x && pos.z == self.z
+    }
+
+    fn heuristic(&self, pos: Vector3i) -> f64 {
+        let xd = (pos.x - self.x) as f64;
+        let zd = (pos.z - self.z) as f64;
+        (xd * xd + zd * zd).sqrt()
+    }
+}
+
+/// Reach a Y level, anywhere horizontally.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GoalYLevel(pub i32);
+
+impl Goal<Vector3i> for GoalYLevel {
+    fn is_goal(&self, pos: Vector3i) -> bool {
+        pos.y == self.0
+    }
+
+    fn heuristic(&self, pos: Vector3i) -> f64 {
+        (pos.y - self.0).abs() as f64
+    }
+}
@@ -1,14 +1,16 @@
 use crate::binding::jni::JNICompatible;
+use crate::binding::util::{enum_constant, enum_name};
 use crate::pathing::algorithm::GraphPosition;
 use crate::vec3i;
-use eyre::Result;
-use jni::objects::{JObject, JValueGen};
+use eyre::{eyre, Result};
+use jni::objects::JObject;
 use jni::JNIEnv;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use std::ops::{Add, Sub};
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize, Ord, PartialOrd, Eq, PartialEq, Hash)]
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Ord, PartialOrd, Eq, PartialEq, Hash, JNICompatible)]
+#[jni(class = "com/genericbadname/s4mc/math/Vector3i")]
 pub struct Vector3i {
     pub x: i32,
     pub y: i32,
@@ -67,33 +69,82 @@ impl Display for Vector3i {
     }
 }
 
-impl <'local> JNICompatible<'local> for Vector3i {
-    const CLASS: &'static str = "com/genericbadname/s4mc/math/Vector3i";
+impl GraphPosition for Vector3i {
+    /// Packs the same way as Minecraft's `BlockPos`: 26 bits for X and Z, 12 bits for Y. Keys are
+    /// only unique within those bits, that is for X and Z in `-2^25..2^25` and Y in `-2048..2048`,
+    /// which covers every block a world can have. Positions outside of that alias others.
+    fn pack(&self) -> i64 {
+        debug_assert!((-2048..2048).contains(&self.y) && (-(1 << 25)..1 << 25).contains(&self.x)
+                      && (-(1 << 25)..1 << 25).contains(&self.z), "{} is out of the packable range", self);
+        ((self.x as i64 & 0x3FF_FFFF) << 38) | ((self.z as i64 & 0x3FF_FFFF) << 12) | (self.y as i64 & 0xFFF)
+    }
+
+    fn distance(a: &Self, b: &Self) -> f64 {
+        a.distance_squared(b)
+    }
+}
+
+/// The six sides of a block, named the way Minecraft names directions.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Face {
+    Down,
+    Up,
+    North,
+    South,
+    West,
+    East
+}
+
+impl Face {
+    pub const ALL: [Face; 6] = [Face::Down, Face::Up, Face::North, Face::South, Face::West, Face::East];
+
+    /// Returns the offset to the neighboring block on this side.
+    pub const fn offset(&self) -> Vector3i {
+        match self {
+            Face::Down => vec3i!(0, -1, 0),
+            Face::Up => vec3i!(0, 1, 0),
+            Face::North => vec3i!(0, 0, -1),
+            Face::South => vec3i!(0, 0, 1),
+            Face::West => vec3i!(-1, 0, 0),
+            Face::East => vec3i!(1, 0, 0)
+        }
+    }
+
+    /// Returns the side an offset points at, if it points at a neighbor.
+    pub fn from_offset(offset: Vector3i) -> Option<Face> {
+        Self::ALL.into_iter().find(|face| face.offset() == offset)
+    }
+
+    /// Name of the matching constant in the Java enum.
+    const fn java_name(&self) -> &'static str {
+        match self {
+            Face::Down => "DOWN",
+            Face::Up => "UP",
+            Face::North => "NORTH",
+            Face::South => "SOUTH",
+            Face::West => "WEST",
+            Face::East => "EAST"
+        }
+    }
+}
+
+impl <'local> JNICompatible<'local> for Face {
+    const CLASS: &'static str = "com/genericbadname/s4mc/math/Face";
 
     fn to_jni(&self, env: &mut JNIEnv<'local>) -> Result<JObject<'local>> {
-        let vec3i_class = env.find_class(Self::CLASS)?;
-        Ok(env.new_object(vec3i_class, "(III)V", &[
-            JValueGen::Int(self.x),
-            JValueGen::Int(self.y),
-            JValueGen::Int(self.z)
-        ])?)
+        enum_constant(env, Self::CLASS, self.java_name())
     }
 
     fn from_jni(env: &mut JNIEnv<'local>, object: JObject<'local>) -> Result<Self>
     where
         Self: Sized
     {
-        let xv = env.call_method(&object, "x", "()I", &[])?.i()?;
-        let yv = env.call_method(&object, "y", "()I", &[])?.i()?;
-        let zv = env.call_method(&object, "z", "()I", &[])?.i()?;
-
-        Ok(vec3i!(xv, yv, zv))
-    }
-}
+        let name = enum_name(env, &object)?;
 
-impl GraphPosition for Vector3i {
-    fn distance(a: &Self, b: &Self) -> f64 {
-        a.distance_squared(b)
+        Self::ALL.into_iter()
+            .find(|face| face.java_name() == name)
+            .ok_or_else(|| eyre!("Unknown face {}", name))
     }
 }
 
@@ -161,6 +212,10 @@ impl Display for Vector2i {
 }
 
 impl GraphPosition for Vector2i {
+    fn pack(&self) -> i64 {
+        ((self.x as i64) << 32) | (self.y as u32 as i64)
+    }
+
     fn distance(a: &Self, b: &Self) -> f64 {
         a.distance_squared(b)
     }
@@ -3,6 +3,11 @@ pub mod algorithm;
 pub mod math;
 pub mod action;
 pub mod world;
+pub mod chunk;
+pub mod block;
+pub mod goal;
+pub mod agent;
+pub mod pool;
 
 // TODO: honestly, replace this with a const fn.
 /// Creates a `Vector3i` without needing to invoke the constructor directly.
@@ -0,0 +1,185 @@
+use crate::pathing::agent::Agent;
+use crate::pathing::algorithm::{CancelToken, GraphPosition, PathCalculator};
+use crate::pathing::data::PathResult;
+use crate::pathing::goal::Goal;
+use crate::pathing::world::Space;
+use dashmap::DashMap;
+use eyre::{eyre, Result};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// Identifies a request made to a `PathPool`. Never reused, and never `0`.
+pub type RequestId = u64;
+
+/// Called on the worker thread with the outcome of a request, instead of keeping it around to be
+/// polled.
+pub type Callback<P> = Box<dyn FnOnce(RequestId, Result<PathResult<P>>) + Send>;
+
+/// Where a request made to a `PathPool` is at.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RequestStatus {
+    /// Waiting for a worker, or being calculated.
+    Pending,
+    /// Finished, the result can be taken.
+    Done
+}
+
+/// A request waiting for a worker.
+struct Job<P, S> where P: GraphPosition, S: Space<P> {
+    id: RequestId,
+    calc: Arc<Mutex<PathCalculator<P, S>>>,
+    start: P,
+    goal: Box<dyn Goal<P> + Send>,
+    agent: Agent,
+    callback: Option<Callback<P>>
+}
+
+/// A request that has been handed out an ID.
+struct Request<P> where P: GraphPosition {
+    cancel: CancelToken,
+    /// Set once finished, unless the request has a callback.
+    result: Option<Result<PathResult<P>>>
+}
+
+/// Worker threads calculating paths in the background. Requests return an ID right away, which can
+/// be polled for the result or cancelled. Calculators are locked for as long as they are in use, so
+/// requests on the same calculator run one after the other, while ones on different calculators
+/// run in parallel.
+pub struct PathPool<P, S> where P: GraphPosition, S: Space<P> {
+    /// Feeds jobs to the workers. Dropping it tells them to stop once the queue is empty.
+    sender: Option<Sender<Job<P, S>>>,
+    workers: Vec<JoinHandle<()>>,
+    requests: Arc<DashMap<RequestId, Request<P>>>,
+    next_id: AtomicU64
+}
+
+impl <P, S> PathPool<P, S> where P: GraphPosition + Send + Sync + 'static, S: Space<P> + Send + 'static {
+    /// Starts a pool with `threads` workers, at least one.
+    pub fn new(threads: usize) -> PathPool<P, S> {
+        let (sender, receiver) = channel::<Job<P, S>>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let requests = Arc::new(DashMap::new());
+
+        let workers = (0..threads.max(1))
+            .map(|idx| {
+                let receiver = Arc::clone(&receiver);
+                let requests = Arc::clone(&requests);
+                thread::Builder::new()
+                    .name(format!("s4rs-pathing-{}", idx))
+                    .spawn(move || Self::work(&receiver, &requests))
+                    .expect("Failed to spawn a pathing worker")
+            })
+            .collect();
+
+        PathPool {
+            sender: Some(sender),
+            workers,
+            requests,
+            next_id: AtomicU64::new(1)
+        }
+    }
+
+    /// Queues a calculation from `start` to `goal` on `calc` for `agent`, returning its ID. Without
+    /// a `callback`, the result is kept until taken with `take`.
+    pub fn submit(
+        &self,
+        calc: Arc<Mutex<PathCalculator<P, S>>>,
+        start: P,
+        goal: Box<dyn Goal<P> + Send>,
+        agent: Agent,
+        callback: Option<Callback<P>>
+    ) -> Result<RequestId> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.requests.insert(id, Request { cancel: CancelToken::new(), result: None });
+
+        let job = Job { id, calc, start, goal, agent, callback };
+        let sent = self.sender.as_ref().is_some_and(|sender| sender.send(job).is_ok());
+        if !sent {
+            self.requests.remove(&id);
+            return Err(eyre!("Path pool has shut down"))
+        }
+
+        Ok(id)
+    }
+
+    /// Returns where a request is at, or `Option::None` if there is no such request (anymore).
+    pub fn poll(&self, id: RequestId) -> Option<RequestStatus> {
+        self.requests.get(&id).map(|request| match request.result {
+            Some(_) => RequestStatus::Done,
+            None => RequestStatus::Pending
+        })
+    }
+
+    /// Removes and returns the result of a finished request. Returns `Option::None` if it hasn't
+    /// finished yet, or if there is no such request.
+    pub fn take(&self, id: RequestId) -> Option<Result<PathResult<P>>> {
+        self.requests.remove_if(&id, |_, request| request.result.is_some())
+            .and_then(|(_, request)| request.result)
+    }
+
+    /// Asks a request to stop. It still finishes, with a cancelled result, which has to be taken
+    /// like any other. Returns whether there was such a request.
+    pub fn cancel(&self, id: RequestId) -> bool {
+        self.requests.get(&id)
+            .map(|request| request.cancel.cancel())
+            .is_some()
+    }
+
+    /// Body of every worker thread: takes jobs off the queue until it's closed.
+    fn work(receiver: &Mutex<Receiver<Job<P, S>>>, requests: &DashMap<RequestId, Request<P>>) {
+        loop {
+            // the lock is only held while waiting, not while calculating
+            let job = match receiver.lock() {
+                Ok(receiver) => receiver.recv(),
+                Err(_) => return
+            };
+            let Ok(job) = job else { return };
+
+            let Some(cancel) = requests.get(&job.id).map(|request| request.cancel.clone()) else { continue };
+            // a panicking calculation shouldn't take the worker down with it, or leave the request
+            // pending forever
+            let result = panic::catch_unwind(AssertUnwindSafe(|| Self::run(&job, cancel)))
+                .unwrap_or_else(|_| Err(eyre!("Path calculation panicked")));
+
+            match job.callback {
+                Some(callback) => {
+                    requests.remove(&job.id);
+                    callback(job.id, result);
+                }
+                None => {
+                    if let Some(mut request) = requests.get_mut(&job.id) {
+                        request.result = Some(result);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Calculates the path for a single job.
+    fn run(job: &Job<P, S>, cancel: CancelToken) -> Result<PathResult<P>> {
+        // no need to wait for the calculator if nobody wants the result anymore
+        if cancel.is_cancelled() { return Ok(PathResult::cancelled()) }
+
+        let mut calc = job.calc.lock()
+            .map_err(|_| eyre!("Calculator was poisoned by an earlier panic"))?;
+        calc.set_agent(job.agent);
+        calc.set_cancel_token(cancel);
+        let result = calc.calculate_to_goal(job.start, job.goal.as_ref());
+        // a token left in place would cancel whatever runs on this calculator next
+        calc.set_cancel_token(CancelToken::new());
+        result
+    }
+}
+
+impl <P, S> Drop for PathPool<P, S> where P: GraphPosition, S: Space<P> {
+    fn drop(&mut self) {
+        // closing the queue stops the workers once it's empty
+        self.sender.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
@@ -1,26 +1,594 @@
 use crate::config::Configuration;
-use crate::pathing::math::{Vector2i, Vector3i};
-use std::rc::Rc;
+use crate::pathing::action::{fall_damage, Interaction, MoveAction, MoveKind, FALL_COST_PER_BLOCK};
+use crate::pathing::agent::{Agent, TICKS_PER_BLOCK};
+use crate::pathing::algorithm::GraphPosition;
+use crate::pathing::block::{BlockClass, BlockRegistry, Climb, Fluid, Openable};
+use crate::pathing::chunk::{local_pos_of, section_pos_of, BlockState, ChunkSection};
+use crate::pathing::data::PathState;
+use crate::pathing::math::{Face, Vector2i, Vector3i};
+use crate::{vec2i, vec3i};
+use dashmap::DashMap;
+use eyre::{eyre, Result};
+use std::sync::Arc;
 
 /// Stores data about a space from which nodes are sampled. A `Space` can be of N-dimensions.
-pub trait Space<P> where P: Copy {
+pub trait Space<P> where P: GraphPosition {
     /// Returns the cost to move to this type of material in the world.
     fn material_cost(&self, pos: P) -> f64;
+
+    /// Works out where performing `action` from `from` ends up and what it costs for `agent`, with
+    /// `state` left over from the path so far. Returns `Option::None` if it can't be performed at
+    /// all. By default, the action lands at its offset
+    /// and costs its base cost plus the material cost of the destination, regardless of the kind
+    /// of move. Corners aren't checked, so spaces with diagonal moves need to do that themselves.
+    fn evaluate(&self, from: P, action: &MoveAction<P>, _agent: &Agent, _state: &PathState) -> Option<MoveResult<P>> {
+        let dest = from + action.offset;
+        Some(MoveResult::new(dest, action.base_cost() + self.material_cost(dest)))
+    }
+}
+
+/// Lets several calculators share the same `Space`, like a world that's kept loaded between
+/// requests.
+impl <P, S> Space<P> for Arc<S> where P: GraphPosition, S: Space<P> {
+    fn material_cost(&self, pos: P) -> f64 {
+        (**self).material_cost(pos)
+    }
+
+    fn evaluate(&self, from: P, action: &MoveAction<P>, agent: &Agent, state: &PathState) -> Option<MoveResult<P>> {
+        (**self).evaluate(from, action, agent, state)
+    }
+}
+
+/// Where a move ended up, and what it took to get there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveResult<P> where P: GraphPosition {
+    /// The position the move lands on. For most moves this is just the offset from the starting
+    /// position, but some (like falls) can only find out by looking at the world.
+    pub dest: P,
+    /// Total cost of the move.
+    pub cost: f64,
+    /// What has to be done to the world to perform the move, in order.
+    pub interactions: Vec<Interaction<P>>,
+    /// What the entity has left after the move, or `Option::None` if the move doesn't use
+    /// anything up.
+    pub state: Option<PathState>
+}
+
+impl <P> MoveResult<P> where P: GraphPosition {
+    pub const fn new(dest: P, cost: f64) -> Self {
+        Self { dest, cost, interactions: Vec::new(), state: None }
+    }
+
+    pub fn with_state(mut self, state: PathState) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    pub fn with_interactions(mut self, interactions: Vec<Interaction<P>>) -> Self {
+        self.interactions = interactions;
+        self
+    }
 }
 
+/// A three-dimensional space made out of Minecraft-style chunk sections. Sections are keyed by
+/// their section coordinates (block coordinates shifted right by 4), and anything outside of a
+/// loaded section is treated as impassable.
 pub struct VoxelSpace {
+    /// Loaded chunk sections.
+    sections: DashMap<Vector3i, ChunkSection>,
+    /// Classifications for the block states stored in `sections`.
+    registry: Arc<BlockRegistry>,
+    /// General configuration values for the pathfinding system.
+    config: Configuration
+}
+
+impl VoxelSpace {
+    pub fn new(config: Configuration, registry: Arc<BlockRegistry>) -> VoxelSpace {
+        VoxelSpace {
+            sections: DashMap::new(),
+            registry,
+            config
+        }
+    }
+
+    /// Returns the registry used to classify blocks.
+    pub fn registry(&self) -> &BlockRegistry {
+        &self.registry
+    }
+
+    /// Returns the configuration moves through this world are priced with.
+    pub fn config(&self) -> &Configuration {
+        &self.config
+    }
+
+    /// Loads a section at the given section coordinates, replacing (and returning) any section
+    /// that was already there.
+    pub fn load_section(&self, section_pos: Vector3i, section: ChunkSection) -> Option<ChunkSection> {
+        self.sections.insert(section_pos, section)
+    }
+
+    /// Replaces an already loaded section, returning the previous one. Fails if nothing was loaded
+    /// at `section_pos`.
+    pub fn replace_section(&self, section_pos: Vector3i, section: ChunkSection) -> Result<ChunkSection> {
+        let mut loaded = self.sections.get_mut(&section_pos)
+            .ok_or_else(|| eyre!("No section is loaded at {}", section_pos))?;
+        Ok(std::mem::replace(loaded.value_mut(), section))
+    }
+
+    /// Unloads the section at the given section coordinates, returning it if it was loaded.
+    pub fn unload_section(&self, section_pos: Vector3i) -> Option<ChunkSection> {
+        self.sections.remove(&section_pos).map(|(_, section)| section)
+    }
+
+    /// Unloads every section of the chunk column at chunk coordinates `x`, `z`. Returns the amount
+    /// of sections that were unloaded.
+    pub fn unload_chunk(&self, x: i32, z: i32) -> usize {
+        let before = self.sections.len();
+        self.sections.retain(|pos, _| pos.x != x || pos.z != z);
+        before - self.sections.len()
+    }
+
+    /// Returns whether a section is loaded at the given section coordinates.
+    pub fn is_loaded(&self, section_pos: Vector3i) -> bool {
+        self.sections.contains_key(&section_pos)
+    }
+
+    /// Returns the amount of currently loaded sections.
+    pub fn loaded_sections(&self) -> usize {
+        self.sections.len()
+    }
 
+    /// Gets the block state at a block position, or `Option::None` if its section isn't loaded.
+    pub fn get_block(&self, pos: Vector3i) -> Option<BlockState> {
+        self.sections.get(&section_pos_of(pos))
+            .map(|section| section.get(local_pos_of(pos)))
+    }
+
+    /// Gets the class of the block at a block position. The outer `Option` is `Option::None` if its
+    /// section isn't loaded, the inner one if the block state isn't registered.
+    pub fn get_class(&self, pos: Vector3i) -> Option<Option<BlockClass>> {
+        self.get_block(pos).map(|state| self.registry.class_of(state))
+    }
+
+    /// Sets the block state at a block position, returning the previous state. Fails if the
+    /// section containing `pos` isn't loaded.
+    pub fn set_block(&self, pos: Vector3i, state: BlockState) -> Result<BlockState> {
+        let mut section = self.sections.get_mut(&section_pos_of(pos))
+            .ok_or_else(|| eyre!("Tried to set a block at {} in an unloaded section", pos))?;
+        Ok(section.set(local_pos_of(pos), state))
+    }
+
+    /// Compacts the palettes of every loaded section. Worth running after a large batch of block
+    /// updates, as palettes only ever grow while blocks are being set.
+    pub fn compact(&self) {
+        self.sections.iter_mut().for_each(|mut section| section.compact());
+    }
+
+    /// Returns the approximate amount of memory used by the loaded sections, in bytes.
+    pub fn memory_usage(&self) -> usize {
+        self.sections.iter()
+            .map(|section| size_of::<Vector3i>() + section.memory_usage())
+            .sum()
+    }
+
+    /// Removes every loaded section.
+    pub fn clear(&self) {
+        self.sections.clear();
+    }
 }
 
 impl VoxelSpace {
-    pub fn new() -> VoxelSpace {
-        VoxelSpace {}
+    /// Returns whether the block at `pos` can be stood on.
+    fn is_standable(&self, pos: Vector3i) -> bool {
+        match self.get_class(pos) {
+            Some(Some(BlockClass::Solid | BlockClass::Partial | BlockClass::Climbable(Climb::Scaffolding))) => true,
+            Some(Some(BlockClass::Openable(Openable::Trapdoor | Openable::IronTrapdoor))) => !self.is_open(pos),
+            _ => false
+        }
+    }
+
+    /// Returns whether an entity with its feet at `feet` has something to stand on under any part
+    /// of its footprint.
+    fn has_floor(&self, feet: Vector3i, agent: &Agent) -> bool {
+        self.footprint(feet + vec3i!(0, -1, 0), agent).into_iter().any(|pos| self.is_standable(pos))
+    }
+
+    /// Returns whether an entity with its feet at `feet` stays there, either by standing on the
+    /// block below, by holding on to a climbable or by floating in a liquid.
+    fn is_supported(&self, feet: Vector3i, agent: &Agent) -> bool {
+        self.has_floor(feet, agent) || self.climb_at(feet).is_some() || self.is_liquid(feet)
+    }
+
+    /// Returns every block on the level of `pos` taken up by the entity's footprint, with `pos`
+    /// as its corner.
+    fn footprint(&self, pos: Vector3i, agent: &Agent) -> Vec<Vector3i> {
+        let width = agent.hitbox.width_blocks();
+        (0..width)
+            .flat_map(|dx| (0..width).map(move |dz| pos + vec3i!(dx, 0, dz)))
+            .collect()
+    }
+
+    /// Returns the kind of climbable at `pos`, if there is one.
+    fn climb_at(&self, pos: Vector3i) -> Option<Climb> {
+        match self.get_class(pos) {
+            Some(Some(BlockClass::Climbable(climb))) => Some(climb),
+            _ => None
+        }
+    }
+
+    /// Returns the cost of occupying the `height` blocks starting at `feet` and going up, across
+    /// the entity's whole footprint, which is that of the most expensive block. Returns
+    /// `Option::None` if any of them are impassable.
+    fn body_cost(&self, feet: Vector3i, height: i32, agent: &Agent) -> Option<f64> {
+        let mut cost: f64 = 0.0;
+        for dy in 0..height {
+            for pos in self.footprint(feet + vec3i!(0, dy, 0), agent) {
+                cost = cost.max(self.passable_cost(pos, agent)?);
+            }
+        }
+
+        Some(cost)
+    }
+
+    /// Like `body_cost`, but closed doors, trapdoors and fence gates the agent can open by hand
+    /// don't block the way. Also returns what has to be opened.
+    fn body_cost_opening(&self, feet: Vector3i, height: i32, agent: &Agent) -> Option<(f64, Vec<Interaction<Vector3i>>)> {
+        let mut cost: f64 = 0.0;
+        let mut opened = Vec::new();
+        let cells = (0..height).flat_map(|dy| self.footprint(feet + vec3i!(0, dy, 0), agent));
+        for pos in cells {
+            let Some(openable) = self.openable_at(pos).filter(|_| !self.is_open(pos)) else {
+                cost = cost.max(self.passable_cost(pos, agent)?);
+                continue
+            };
+            if !openable.by_hand() { return None }
+
+            // both halves of a door open together, so only the lower one is interacted with
+            let upper_half = self.get_block(pos)
+                .and_then(|state| self.registry.get(state))
+                .is_some_and(|info| info.property("half") == Some("upper"));
+            let target = if upper_half { pos + vec3i!(0, -1, 0) } else { pos };
+            if !opened.contains(&Interaction::Open(target)) {
+                opened.push(Interaction::Open(target));
+            }
+            cost = cost.max(1.0);
+        }
+
+        Some((cost, opened))
+    }
+
+    /// Returns the material cost of moving through `pos`, or `Option::None` if the agent can't.
+    /// Lava is only passable for agents that are resistant to fire.
+    fn passable_cost(&self, pos: Vector3i, agent: &Agent) -> Option<f64> {
+        if !agent.fire_resistant && self.is_lava(pos) { return None }
+        if self.openable_at(pos).is_some() && !self.is_open(pos) { return None }
+        let material = self.material_cost(pos);
+        if material >= self.config.cost_inf { return None }
+        Some(material)
+    }
+
+    /// Walks off into the column at `from + action.offset` and drops down to whatever is below.
+    /// Only drops of two blocks or more are falls, anything shorter is a `MoveKind::Descend`.
+    fn evaluate_fall(&self, from: Vector3i, action: &MoveAction<Vector3i>, agent: &Agent) -> Option<MoveResult<Vector3i>> {
+        let below = vec3i!(0, -1, 0);
+        let mut feet = from + action.offset;
+        let mut body = self.body_cost(feet, agent.hitbox.height_blocks(), agent)?;
+
+        // liquids and climbables break the fall wherever they are, so there's no need to go any
+        // further
+        let caught = |feet: Vector3i| self.is_liquid(feet) || self.climb_at(feet).is_some();
+        while !caught(feet) && !self.has_floor(feet, agent) {
+            feet = feet + below;
+            // unloaded space is impassable, so this can't run off the bottom of the world
+            body = body.max(self.body_cost(feet, 1, agent)?);
+        }
+
+        let height = from.y - feet.y;
+        if height < 2 { return None }
+
+        let damage = if caught(feet) {
+            0.0
+        } else {
+            let multiplier = self.footprint(feet + below, agent).into_iter()
+                .find(|pos| self.is_standable(*pos))
+                .and_then(|pos| self.get_block(pos))
+                .and_then(|state| self.registry.get(state))
+                .map_or(1.0, |info| info.fall_damage);
+            fall_damage(height, multiplier)
+        };
+        if damage >= self.config.max_health { return None }
+
+        let cost = action.cost as f64 + body + height as f64 * FALL_COST_PER_BLOCK
+            + damage * self.config.damage_cost;
+        Some(MoveResult::new(feet, cost))
+    }
+
+    /// Jumps from `from` across a gap to `from + action.offset`. The whole arc has to be clear: the
+    /// entity's body plus a block of jump headroom over the take-off, every column of the gap and
+    /// the landing.
+    fn evaluate_parkour(&self, from: Vector3i, action: &MoveAction<Vector3i>, agent: &Agent) -> Option<MoveResult<Vector3i>> {
+        if !self.config.allow_parkour { return None }
+        // there's no jumping off a ladder or out of water
+        if !self.has_floor(from, agent) { return None }
+
+        let offset = action.offset;
+        let dir = vec3i!(offset.x.signum(), 0, offset.z.signum());
+        let distance = offset.x.abs().max(offset.z.abs());
+        let dest = from + offset;
+        let height = agent.hitbox.height_blocks();
+
+        // anything that could just be walked over isn't a gap
+        if self.has_floor(from + dir, agent) { return None }
+
+        let mut body = self.body_cost(from + vec3i!(0, height, 0), 1, agent)?;
+        let mut column = from;
+        for _ in 1..distance {
+            column = column + dir;
+            body = body.max(self.body_cost(column, height + 1, agent)?);
+        }
+
+        // the top of the arc carries over into the landing column on a level jump
+        let landing_height = if offset.y > 0 { height } else { height + 1 };
+        body = body.max(self.body_cost(dest, landing_height, agent)?);
+        if !self.has_floor(dest, agent) { return None }
+
+        Some(MoveResult::new(dest, action.base_cost() + body + self.config.parkour_cost))
+    }
+
+    /// Breaks through whatever is in the way of `from + action.offset` and moves into it. Only
+    /// blocks that actually have to go are broken, and at least one has to, as walking into an
+    /// open space is left to the other moves.
+    fn evaluate_tunnel(&self, from: Vector3i, action: &MoveAction<Vector3i>, agent: &Agent) -> Option<MoveResult<Vector3i>> {
+        if !self.config.allow_breaking { return None }
+
+        let dest = from + action.offset;
+        let above = vec3i!(0, 1, 0);
+        // digging down only needs the blocks underfoot gone, the rest of the body follows into the
+        // space the feet were in. Cells are listed top-down, which is the order they're broken in
+        let height = if action.offset.y < 0 { 1 } else { agent.hitbox.height_blocks() };
+        let cells: Vec<Vector3i> = (0..height).rev()
+            .flat_map(|dy| self.footprint(dest + vec3i!(0, dy, 0), agent))
+            .collect();
+
+        let mut interactions = Vec::new();
+        let mut body: f64 = 0.0;
+        let mut mining = 0.0;
+        for cell in cells {
+            if let Some(material) = self.passable_cost(cell, agent) {
+                body = body.max(material);
+                continue
+            }
+
+            // whatever is above falls into the gap, unless it was broken first
+            let broken_above = interactions.contains(&Interaction::Break(cell + above));
+            if !broken_above && self.has_gravity(cell + above) { return None }
+
+            mining += self.break_cost(cell, agent)?;
+            body = body.max(1.0);
+            interactions.push(Interaction::Break(cell));
+        }
+
+        if interactions.is_empty() { return None }
+        if !self.has_floor(dest, agent) { return None }
+
+        Some(MoveResult::new(dest, action.base_cost() + body + mining).with_interactions(interactions))
+    }
+
+    /// Returns the cost of breaking the block at `pos`, or `Option::None` if it can't be broken,
+    /// or if breaking it would let a liquid next to it flow in.
+    fn break_cost(&self, pos: Vector3i, agent: &Agent) -> Option<f64> {
+        if self.is_liquid(pos) { return None }
+        let info = self.get_block(pos).and_then(|state| self.registry.get(state))?;
+        let ticks = agent.break_ticks(info)?;
+
+        // liquids never flow upwards, so the block below doesn't matter
+        let neighbors = [vec3i!(0, 1, 0), vec3i!(1, 0, 0), vec3i!(-1, 0, 0), vec3i!(0, 0, 1), vec3i!(0, 0, -1)];
+        if neighbors.iter().any(|offset| self.is_liquid(pos + *offset)) { return None }
+
+        Some(ticks / TICKS_PER_BLOCK + self.config.break_cost)
+    }
+
+    /// Places a block under `from + action.offset` and walks onto it. The block is placed against
+    /// the side of the one being stood on, so there has to be one, either in the world or placed by
+    /// the move before.
+    fn evaluate_bridge(&self, from: Vector3i, action: &MoveAction<Vector3i>, agent: &Agent, state: &PathState) -> Option<MoveResult<Vector3i>> {
+        if !self.config.allow_placing || state.blocks == 0 { return None }
+        // hanging on to a ladder or swimming leaves nothing to place against
+        if !state.on_placed && !self.has_floor(from, agent) { return None }
+        if self.is_liquid(from) { return None }
+
+        let below = vec3i!(0, -1, 0);
+        let dest = from + action.offset;
+        let floor = dest + below;
+        if !self.can_place_at(floor) { return None }
+        let body = self.body_cost(dest, agent.hitbox.height_blocks(), agent)?;
+        let face = Face::from_offset(from - dest)?;
+
+        Some(MoveResult::new(dest, action.base_cost() + body + self.config.place_cost)
+            .with_interactions(vec![Interaction::Place { pos: floor, face }])
+            .with_state(state.place_block()))
+    }
+
+    /// Jumps up and places a block where the entity's feet were, on top of the one it was standing
+    /// on.
+    fn evaluate_pillar(&self, from: Vector3i, action: &MoveAction<Vector3i>, agent: &Agent, state: &PathState) -> Option<MoveResult<Vector3i>> {
+        if !self.config.allow_placing || state.blocks == 0 { return None }
+        if !state.on_placed && !self.has_floor(from, agent) { return None }
+
+        let dest = from + action.offset;
+        if !self.can_place_at(from) { return None }
+        let body = self.body_cost(dest, agent.hitbox.height_blocks(), agent)?;
+
+        Some(MoveResult::new(dest, action.base_cost() + body + self.config.place_cost)
+            .with_interactions(vec![Interaction::Place { pos: from, face: Face::Down }])
+            .with_state(state.place_block()))
+    }
+
+    /// Climbs a block up or down. Climbing ends where there's nothing left to hold on to, unless
+    /// there's something to stand on, like on top of scaffolding. Getting off the top of a ladder
+    /// is left to the other moves: stepping onto the ledge next to the topmost rung is an ascend,
+    /// which already checks for the headroom it takes.
+    fn evaluate_climb(&self, from: Vector3i, action: &MoveAction<Vector3i>, agent: &Agent) -> Option<MoveResult<Vector3i>> {
+        let dest = from + action.offset;
+        let climb = if action.offset.y > 0 {
+            // going up means pulling up on whatever is already being held
+            if !self.is_supported(dest, agent) { return None }
+            self.climb_at(from)?
+        } else {
+            // going down can start from on top of scaffolding
+            self.climb_at(dest)?
+        };
+        let body = self.body_cost(dest, agent.hitbox.height_blocks(), agent)?;
+
+        Some(MoveResult::new(dest, action.base_cost() * self.config.climb_cost.of(climb) + body))
+    }
+
+    /// Works out where a move ends up and what it costs, before breathing is accounted for.
+    fn evaluate_move(&self, from: Vector3i, action: &MoveAction<Vector3i>, agent: &Agent, state: &PathState) -> Option<MoveResult<Vector3i>> {
+        let dest = from + action.offset;
+        let height = agent.hitbox.height_blocks();
+
+        let (body, opened) = match action.kind {
+            MoveKind::Traverse => self.body_cost_opening(dest, height, agent)?,
+            MoveKind::Diagonal => {
+                let offset = action.offset;
+                let blocked = |side: Vector3i| self.body_cost(from + side, height, agent).is_none();
+                if !self.config.corner_cutting.allows(blocked(vec3i!(offset.x, 0, 0)), blocked(vec3i!(0, 0, offset.z))) {
+                    return None
+                }
+                // doorways can't be squeezed through diagonally
+                (self.body_cost(dest, height, agent)?, Vec::new())
+            }
+            MoveKind::Ascend => {
+                // jumping needs an extra block of headroom above the starting position
+                self.body_cost(from + vec3i!(0, height, 0), 1, agent)?;
+                self.body_cost_opening(dest, height, agent)?
+            }
+            MoveKind::Descend => {
+                // the entity walks over the edge before dropping, so its whole body has to fit
+                // above the destination too
+                self.body_cost_opening(dest, height + 1, agent)?
+            }
+            MoveKind::Fall => return self.evaluate_fall(from, action, agent),
+            MoveKind::Parkour => return self.evaluate_parkour(from, action, agent),
+            MoveKind::Tunnel => return self.evaluate_tunnel(from, action, agent),
+            MoveKind::Bridge => return self.evaluate_bridge(from, action, agent, state),
+            MoveKind::Pillar => return self.evaluate_pillar(from, action, agent, state),
+            MoveKind::Climb => return self.evaluate_climb(from, action, agent),
+            MoveKind::Swim => return self.evaluate_swim(from, action, agent)
+        };
+
+        if !self.is_supported(dest, agent) { return None }
+
+        let cost = action.base_cost() + body + opened.len() as f64 * self.config.open_cost;
+        Some(MoveResult::new(dest, cost).with_interactions(opened))
+    }
+
+    /// Swims a block through a liquid. Costs depend on whether the head ends up underwater, and
+    /// flowing water pushes back. Swimming speed already prices in being in water, so only what the
+    /// body moves through beyond that (lava, say) is added on top. Getting out of the liquid is left
+    /// to the other moves.
+    fn evaluate_swim(&self, from: Vector3i, action: &MoveAction<Vector3i>, agent: &Agent) -> Option<MoveResult<Vector3i>> {
+        let dest = from + action.offset;
+        if !self.is_liquid(dest) { return None }
+        let body = self.body_cost(dest, agent.hitbox.height_blocks(), agent)?;
+        let water = self.config.hazard.multiplier_for(Some(BlockClass::Liquid(Fluid::Water))).unwrap_or(0.0);
+        let hazard = (body - water).max(0.0);
+
+        Some(MoveResult::new(dest, action.base_cost() * self.swim_ticks(dest, agent) / TICKS_PER_BLOCK + hazard))
+    }
+
+    /// Returns the ticks it takes to swim a block into `dest`.
+    fn swim_ticks(&self, dest: Vector3i, agent: &Agent) -> f64 {
+        let swim = self.config.swim_ticks;
+        let ticks = if self.is_submerged(dest, agent) { swim.diving } else { swim.surface };
+        if self.is_flowing(dest) { ticks + swim.current } else { ticks }
+    }
+
+    /// Uses up the air a move takes if it ends with the entity's head underwater, refusing it if
+    /// there isn't enough left. Time spent is estimated from the length of the move, at swimming
+    /// speed for swims and walking speed for anything else. Any move that comes up for air refills
+    /// it.
+    fn breathe(&self, result: MoveResult<Vector3i>, action: &MoveAction<Vector3i>, agent: &Agent, state: &PathState) -> Option<MoveResult<Vector3i>> {
+        // only moves that place a block to stand on say so themselves
+        let after = result.state.unwrap_or(PathState { on_placed: false, ..*state });
+        let air = if self.is_submerged(result.dest, agent) {
+            let per_block = match action.kind {
+                MoveKind::Swim => self.swim_ticks(result.dest, agent),
+                _ => TICKS_PER_BLOCK
+            };
+            let ticks = (action.base_cost() * per_block).round() as u32;
+            after.air.checked_sub(ticks)?
+        } else {
+            agent.air_supply
+        };
+
+        let after = PathState { air, ..after };
+        if result.state.is_none() && after == *state { return Some(result) }
+        Some(result.with_state(after))
+    }
+
+    /// Returns whether the entity's head is underwater with its feet at `feet`.
+    fn is_submerged(&self, feet: Vector3i, agent: &Agent) -> bool {
+        self.is_liquid(feet + vec3i!(0, agent.hitbox.height_blocks() - 1, 0))
+    }
+
+    /// Returns whether the block at `pos` is flowing water, going by its `level` property: source
+    /// blocks are level 0, anything else has flowed there.
+    fn is_flowing(&self, pos: Vector3i) -> bool {
+        let Some(info) = self.get_block(pos).and_then(|state| self.registry.get(state)) else { return false };
+        info.class == BlockClass::Liquid(Fluid::Water)
+            && info.property("level").is_some_and(|level| level != "0")
+    }
+
+    /// Returns the kind of door, trapdoor or fence gate at `pos`, if there is one.
+    fn openable_at(&self, pos: Vector3i) -> Option<Openable> {
+        match self.get_class(pos) {
+            Some(Some(BlockClass::Openable(openable))) => Some(openable),
+            _ => None
+        }
+    }
+
+    /// Returns whether the block at `pos` is open, going by its `open` property.
+    fn is_open(&self, pos: Vector3i) -> bool {
+        self.get_block(pos)
+            .and_then(|state| self.registry.get(state))
+            .is_some_and(|info| info.property("open") == Some("true"))
+    }
+
+    /// Returns whether a block can be placed at `pos`.
+    fn can_place_at(&self, pos: Vector3i) -> bool {
+        matches!(self.get_class(pos), Some(Some(BlockClass::Air)))
+    }
+
+    /// Returns whether the block at `pos` falls when there's nothing under it.
+    fn has_gravity(&self, pos: Vector3i) -> bool {
+        self.get_block(pos)
+            .and_then(|state| self.registry.get(state))
+            .is_some_and(|info| info.gravity)
+    }
+
+    /// Returns whether the block at `pos` is a liquid.
+    fn is_liquid(&self, pos: Vector3i) -> bool {
+        matches!(self.get_class(pos), Some(Some(BlockClass::Liquid(_))))
+    }
+
+    /// Returns whether the block at `pos` is lava.
+    fn is_lava(&self, pos: Vector3i) -> bool {
+        matches!(self.get_class(pos), Some(Some(BlockClass::Liquid(Fluid::Lava))))
     }
 }
 
 impl Space<Vector3i> for VoxelSpace {
     fn material_cost(&self, pos: Vector3i) -> f64 {
-        todo!()
+        self.get_class(pos)
+            .and_then(|class| self.config.hazard.multiplier_for(class))
+            .unwrap_or(self.config.cost_inf)
+    }
+
+    fn evaluate(&self, from: Vector3i, action: &MoveAction<Vector3i>, agent: &Agent, state: &PathState) -> Option<MoveResult<Vector3i>> {
+        let result = self.evaluate_move(from, action, agent, state)?;
+        self.breathe(result, action, agent, state)
     }
 }
 
@@ -65,4 +633,17 @@ impl Space<Vector2i> for FlatSpace {
 
         self.config.cost_inf
     }
+
+    fn evaluate(&self, from: Vector2i, action: &MoveAction<Vector2i>, _agent: &Agent, _state: &PathState) -> Option<MoveResult<Vector2i>> {
+        let dest = from + action.offset;
+        if action.kind == MoveKind::Diagonal {
+            let offset = action.offset;
+            let blocked = |side: Vector2i| self.material_cost(from + side) >= self.config.cost_inf;
+            if !self.config.corner_cutting.allows(blocked(vec2i!(offset.x, 0)), blocked(vec2i!(0, offset.y))) {
+                return None
+            }
+        }
+
+        Some(MoveResult::new(dest, action.base_cost() + self.material_cost(dest)))
+    }
 }
\ No newline at end of file
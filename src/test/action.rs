@@ -0,0 +1,958 @@
+use crate::config::{Configuration, CornerCutting};
+use crate::pathing::action::{default_moveset, fall_damage, Interaction, MoveAction, MoveKind, Moveset, FALL_COST_PER_BLOCK};
+use crate::pathing::agent::{Agent, Hitbox, Tool, TICKS_PER_BLOCK};
+use crate::pathing::algorithm::PathCalculator;
+use crate::pathing::block::{BlockClass, BlockRegistry, Climb, ToolKind};
+use crate::pathing::chunk::{section_pos_of, ChunkSection};
+use crate::pathing::data::{PathResult, PathState, PathStatus};
+use crate::pathing::math::{Face, Vector3i};
+use crate::pathing::world::{Space, VoxelSpace};
+use crate::vec3i;
+use std::sync::Arc;
+
+/// Block table used by the movement scenarios. Stone has no hardness, so it can't be broken.
+const BLOCKS: &str = r#"[
+    { "id": 1, "name": "minecraft:stone", "class": "solid" },
+    { "id": 2, "name": "minecraft:water", "class": { "liquid": "water" } },
+    { "id": 3, "name": "minecraft:hay_block", "class": "solid", "fall_damage": 0.2 },
+    { "id": 4, "name": "minecraft:dirt", "class": "solid", "hardness": 0.5, "tool": "shovel" },
+    { "id": 5, "name": "minecraft:sand", "class": "solid", "hardness": 0.5, "tool": "shovel", "gravity": true },
+    { "id": 6, "name": "minecraft:cobblestone", "class": "solid", "hardness": 2.0, "tool": "pickaxe", "requires_tool": true },
+    { "id": 7, "name": "minecraft:ladder", "class": { "climbable": "ladder" } },
+    { "id": 8, "name": "minecraft:vine", "class": { "climbable": "vine" } },
+    { "id": 9, "name": "minecraft:scaffolding", "class": { "climbable": "scaffolding" } },
+    { "id": 10, "name": "minecraft:lava", "class": { "liquid": "lava" } },
+    { "id": 11, "name": "minecraft:water", "class": { "liquid": "water" }, "properties": { "level": "3" } },
+    { "id": 12, "name": "minecraft:oak_door", "class": { "openable": "door" }, "properties": { "half": "lower", "open": "false" } },
+    { "id": 13, "name": "minecraft:oak_door", "class": { "openable": "door" }, "properties": { "half": "upper", "open": "false" } },
+    { "id": 14, "name": "minecraft:oak_door", "class": { "openable": "door" }, "properties": { "open": "true" } },
+    { "id": 15, "name": "minecraft:iron_door", "class": { "openable": "iron_door" }, "properties": { "open": "false" } },
+    { "id": 16, "name": "minecraft:oak_trapdoor", "class": { "openable": "trapdoor" }, "properties": { "open": "false" } },
+    { "id": 17, "name": "minecraft:oak_fence_gate", "class": { "openable": "fence_gate" }, "properties": { "open": "false" } }
+]"#;
+
+#[test]
+fn step_up() {
+    let mut scenario = VoxelScenario::new(vec![
+        vec!["######"],
+        vec!["...###"],
+        vec!["......"],
+        vec!["......"]
+    ]);
+
+    let result = scenario.eval_success(vec3i!(0, 1, 0), vec3i!(5, 2, 0));
+    assert_eq!(kinds(&result), vec![MoveKind::Traverse, MoveKind::Traverse, MoveKind::Ascend,
+                                     MoveKind::Traverse, MoveKind::Traverse]);
+}
+
+#[test]
+fn step_up_needs_headroom() {
+    let mut scenario = VoxelScenario::new(vec![
+        vec!["######"],
+        vec!["...###"],
+        vec!["......"],
+        vec!["..#..."]
+    ]);
+
+    scenario.eval_failure(vec3i!(0, 1, 0), vec3i!(5, 2, 0));
+}
+
+#[test]
+fn step_up_too_high() {
+    let mut scenario = VoxelScenario::new(vec![
+        vec!["######"],
+        vec!["...###"],
+        vec!["...###"],
+        vec!["......"],
+        vec!["......"]
+    ]);
+
+    scenario.eval_failure(vec3i!(0, 1, 0), vec3i!(5, 3, 0));
+}
+
+#[test]
+fn step_down() {
+    let mut scenario = VoxelScenario::new(vec![
+        vec!["######"],
+        vec!["...###"],
+        vec!["......"],
+        vec!["......"]
+    ]);
+
+    let result = scenario.eval_success(vec3i!(5, 2, 0), vec3i!(0, 1, 0));
+    assert_eq!(kinds(&result), vec![MoveKind::Traverse, MoveKind::Traverse, MoveKind::Descend,
+                                     MoveKind::Traverse, MoveKind::Traverse]);
+}
+
+#[test]
+fn step_down_needs_headroom() {
+    // the entity has to walk over the ledge before dropping, so its head needs room there
+    let mut scenario = VoxelScenario::new(vec![
+        vec!["######"],
+        vec!["...###"],
+        vec!["......"],
+        vec!["..#..."]
+    ]);
+
+    scenario.eval_failure(vec3i!(5, 2, 0), vec3i!(0, 1, 0));
+}
+
+#[test]
+fn traverse_needs_floor_and_headroom() {
+    let config = Configuration {
+        allow_parkour: false,
+        ..Configuration::new()
+    };
+    let mut gap = VoxelScenario::with_moves(vec![
+        vec!["##.##"],
+        vec!["....."],
+        vec!["....."]
+    ], default_moveset(), config);
+    gap.eval_failure(vec3i!(0, 1, 0), vec3i!(4, 1, 0));
+
+    let mut low_ceiling = VoxelScenario::new(vec![
+        vec!["#####"],
+        vec!["....."],
+        vec!["..#.."]
+    ]);
+    low_ceiling.eval_failure(vec3i!(0, 1, 0), vec3i!(4, 1, 0));
+}
+
+#[test]
+fn step_up_over_obstacle() {
+    // stepping over a single block is cheaper than walking around it
+    let mut scenario = VoxelScenario::new(vec![
+        vec!["#####", "#####", "#####", "#####", "#####"],
+        vec!["..#..", "..#..", "..#..", "..#..", "....."],
+        vec!["....."; 5],
+        vec!["....."; 5]
+    ]);
+
+    let result = scenario.eval_success(vec3i!(0, 1, 0), vec3i!(4, 1, 0));
+    assert_eq!(kinds(&result), vec![MoveKind::Traverse, MoveKind::Ascend, MoveKind::Descend, MoveKind::Traverse]);
+}
+
+#[test]
+fn fall_safe_height() {
+    let mut scenario = VoxelScenario::drop(4, '#', Configuration::new());
+    let result = scenario.eval_success(vec3i!(0, 4, 0), vec3i!(1, 1, 0));
+    assert_eq!(kinds(&result), vec![MoveKind::Fall]);
+}
+
+#[test]
+fn fall_cost() {
+    let config = Configuration::new();
+    let scenario = VoxelScenario::drop(8, '#', config);
+    let fall = MoveAction::of_kind(MoveKind::Fall, 1, vec3i!(1, 0, 0));
+
+    let result = scenario.calc.space().evaluate(vec3i!(0, 8, 0), &fall, &Agent::new(), &PathState::default());
+    assert!(result.is_some(), "Fall was refused");
+    let result = result.unwrap();
+    assert_eq!(result.dest, vec3i!(1, 1, 0), "Fall did not land on the floor");
+    let damage = fall_damage(7, 1.0);
+    assert_eq!(damage, 4.0);
+    assert_eq!(result.cost, 1.0 + 1.0 + 7.0 * FALL_COST_PER_BLOCK + damage * config.damage_cost,
+               "Fall was not priced by height and damage");
+
+    let too_short = scenario.calc.space().evaluate(vec3i!(0, 1, 0), &fall, &Agent::new(), &PathState::default());
+    assert!(too_short.is_none(), "Walking onto level ground was treated as a fall");
+}
+
+#[test]
+fn fall_lethal() {
+    let config = Configuration {
+        max_health: 5.0,
+        ..Configuration::new()
+    };
+
+    // 7 damage
+    let mut stone = VoxelScenario::drop(11, '#', config);
+    stone.eval_failure(vec3i!(0, 11, 0), vec3i!(1, 1, 0));
+
+    // 1.4 damage
+    let mut hay = VoxelScenario::drop(11, 'h', config);
+    hay.eval_success(vec3i!(0, 11, 0), vec3i!(1, 1, 0));
+
+    // 5 damage, exactly enough to kill
+    let mut lethal = VoxelScenario::drop(9, '#', config);
+    lethal.eval_failure(vec3i!(0, 9, 0), vec3i!(1, 1, 0));
+}
+
+#[test]
+fn fall_into_water() {
+    let config = Configuration {
+        max_health: 5.0,
+        ..Configuration::new()
+    };
+    let mut scenario = VoxelScenario::drop(30, '~', config);
+    let result = scenario.eval_success(vec3i!(0, 30, 0), vec3i!(1, 1, 0));
+    assert_eq!(kinds(&result), vec![MoveKind::Fall]);
+
+    let fall = MoveAction::of_kind(MoveKind::Fall, 1, vec3i!(1, 0, 0));
+    let cost = scenario.calc.space().evaluate(vec3i!(0, 30, 0), &fall, &Agent::new(), &PathState::default()).unwrap().cost;
+    assert!(cost < 1.0 + 2.1 + 30.0 * FALL_COST_PER_BLOCK, "Water landing dealt damage");
+}
+
+#[test]
+fn diagonal_walk() {
+    let mut scenario = VoxelScenario::new(vec![
+        vec!["###", "###", "###"],
+        vec!["..."; 3],
+        vec!["..."; 3]
+    ]);
+
+    let result = scenario.eval_success(vec3i!(0, 1, 0), vec3i!(2, 1, 2));
+    assert_eq!(kinds(&result), vec![MoveKind::Diagonal, MoveKind::Diagonal]);
+}
+
+#[test]
+fn diagonal_corner_cutting() {
+    // anything in the way of the entity's body blocks the corner, even a single step
+    let layers = |pillar| vec![
+        vec!["##", "##"],
+        vec![".#", ".."],
+        vec![pillar, ".."],
+        vec!["..", ".."]
+    ];
+    let config = |corner_cutting| Configuration { corner_cutting, ..Configuration::new() };
+
+    let mut forbid = VoxelScenario::with_moves(layers(".#"), default_moveset(), config(CornerCutting::Forbid));
+    let result = forbid.eval_success(vec3i!(0, 1, 0), vec3i!(1, 1, 1));
+    assert_eq!(kinds(&result), vec![MoveKind::Traverse, MoveKind::Traverse], "Cut a blocked corner");
+
+    let mut low = VoxelScenario::with_moves(layers(".."), default_moveset(), config(CornerCutting::Forbid));
+    let result = low.eval_success(vec3i!(0, 1, 0), vec3i!(1, 1, 1));
+    assert_eq!(kinds(&result), vec![MoveKind::Traverse, MoveKind::Traverse], "Cut a corner past a step");
+
+    let mut one_side = VoxelScenario::with_moves(layers(".#"), default_moveset(), config(CornerCutting::AllowOneSide));
+    let result = one_side.eval_success(vec3i!(0, 1, 0), vec3i!(1, 1, 1));
+    assert_eq!(kinds(&result), vec![MoveKind::Diagonal], "Did not cut a corner with a clear side");
+}
+
+#[test]
+fn parkour_gaps() {
+    for gap in 1..=3 {
+        let mut scenario = VoxelScenario::gap(gap, 0, Configuration::new());
+        let result = scenario.eval_success(vec3i!(1, 1, 0), vec3i!(gap + 2, 1, 0));
+        assert_eq!(kinds(&result), vec![MoveKind::Parkour], "Did not jump a {} block gap", gap);
+    }
+
+    let mut too_far = VoxelScenario::gap(4, 0, Configuration::new());
+    too_far.eval_failure(vec3i!(1, 1, 0), vec3i!(6, 1, 0));
+}
+
+#[test]
+fn parkour_rise() {
+    for gap in 1..=2 {
+        let mut scenario = VoxelScenario::gap(gap, 1, Configuration::new());
+        let result = scenario.eval_success(vec3i!(1, 1, 0), vec3i!(gap + 2, 2, 0));
+        assert_eq!(kinds(&result), vec![MoveKind::Parkour], "Did not jump up a {} block gap", gap);
+    }
+
+    let mut too_far = VoxelScenario::gap(3, 1, Configuration::new());
+    too_far.eval_failure(vec3i!(1, 1, 0), vec3i!(5, 2, 0));
+}
+
+#[test]
+fn parkour_arc_clearance() {
+    // the top of the arc, over the gap
+    let mut gap_ceiling = VoxelScenario::new(vec![
+        vec!["##..##"],
+        vec!["......"],
+        vec!["......"],
+        vec!["...#.."]
+    ]);
+    gap_ceiling.eval_failure(vec3i!(1, 1, 0), vec3i!(4, 1, 0));
+
+    // jump headroom over the take-off
+    let mut take_off_ceiling = VoxelScenario::new(vec![
+        vec!["##..##"],
+        vec!["......"],
+        vec!["......"],
+        vec![".#...."]
+    ]);
+    take_off_ceiling.eval_failure(vec3i!(1, 1, 0), vec3i!(4, 1, 0));
+
+    // coming down into the landing
+    let mut landing_ceiling = VoxelScenario::new(vec![
+        vec!["##..##"],
+        vec!["......"],
+        vec!["......"],
+        vec!["....#."]
+    ]);
+    landing_ceiling.eval_failure(vec3i!(1, 1, 0), vec3i!(4, 1, 0));
+}
+
+#[test]
+fn parkour_needs_floor() {
+    let take_off = |below, feet| VoxelScenario::new(vec![
+        vec![below],
+        vec![feet],
+        vec!["......"],
+        vec!["......"]
+    ]);
+
+    let mut standing = take_off("#..##.", "......");
+    let result = standing.eval_success(vec3i!(0, 1, 0), vec3i!(3, 1, 0));
+    assert_eq!(kinds(&result), vec![MoveKind::Parkour]);
+
+    // holding on to a ladder or floating leaves nothing to jump off
+    let mut ladder = take_off("...##.", "H.....");
+    ladder.eval_failure(vec3i!(0, 1, 0), vec3i!(3, 1, 0));
+    let mut water = take_off("~..##.", "~.....");
+    water.eval_failure(vec3i!(0, 1, 0), vec3i!(3, 1, 0));
+}
+
+#[test]
+fn parkour_config() {
+    let disabled = Configuration {
+        allow_parkour: false,
+        ..Configuration::new()
+    };
+    let mut scenario = VoxelScenario::gap(2, 0, disabled);
+    scenario.eval_failure(vec3i!(1, 1, 0), vec3i!(4, 1, 0));
+
+    let config = Configuration::new();
+    let scenario = VoxelScenario::gap(2, 0, config);
+    let jump = MoveAction::of_kind(MoveKind::Parkour, 3, vec3i!(3, 0, 0));
+    let result = scenario.calc.space().evaluate(vec3i!(1, 1, 0), &jump, &Agent::new(), &PathState::default());
+    assert!(result.is_some(), "Parkour jump was refused");
+    assert_eq!(result.unwrap().cost, 3.0 + 1.0 + config.parkour_cost, "Parkour jump was not priced by distance");
+
+    // there's no gap to jump from the pillar's middle
+    let walkable = scenario.calc.space().evaluate(vec3i!(0, 1, 0), &MoveAction::of_kind(MoveKind::Parkour, 2, vec3i!(2, 0, 0)), &Agent::new(), &PathState::default());
+    assert!(walkable.is_none(), "Jumped when walking would do");
+}
+
+#[test]
+fn tunnel_through_wall() {
+    let mut scenario = VoxelScenario::interacting(vec![
+        vec!["#####"],
+        vec!["..d.."],
+        vec!["..d.."],
+        vec!["#####"]
+    ]);
+
+    let result = scenario.eval_success(vec3i!(0, 1, 0), vec3i!(4, 1, 0));
+    assert_eq!(kinds(&result), vec![MoveKind::Traverse, MoveKind::Tunnel, MoveKind::Traverse, MoveKind::Traverse]);
+    let broken: Vec<_> = result.path.iter().flat_map(|n| n.interactions.iter().copied()).collect();
+    assert_eq!(broken, vec![Interaction::Break(vec3i!(2, 2, 0)), Interaction::Break(vec3i!(2, 1, 0))],
+               "Path did not record the blocks to break, top-down");
+    assert_eq!(result.path[2].interactions.len(), 2, "Broken blocks were not recorded on the tunnelling node");
+
+    let config = Configuration {
+        allow_breaking: false,
+        ..Configuration::new()
+    };
+    let mut disabled = VoxelScenario::with_moves(vec![
+        vec!["#####"],
+        vec!["..d.."],
+        vec!["..d.."],
+        vec!["#####"]
+    ], default_moveset(), config);
+    disabled.eval_failure(vec3i!(0, 1, 0), vec3i!(4, 1, 0));
+}
+
+#[test]
+fn tunnel_only_breaks_what_is_in_the_way() {
+    let mut scenario = VoxelScenario::interacting(vec![
+        vec!["#####"],
+        vec!["....."],
+        vec!["..d.."],
+        vec!["#####"]
+    ]);
+
+    let result = scenario.eval_success(vec3i!(0, 1, 0), vec3i!(4, 1, 0));
+    assert_eq!(result.path[2].interactions, vec![Interaction::Break(vec3i!(2, 2, 0))]);
+}
+
+#[test]
+fn tunnel_down() {
+    let mut scenario = VoxelScenario::interacting(vec![
+        vec!["#"],
+        vec!["d"],
+        vec!["d"],
+        vec!["."],
+        vec!["."]
+    ]);
+
+    let result = scenario.eval_success(vec3i!(0, 3, 0), vec3i!(0, 1, 0));
+    assert_eq!(kinds(&result), vec![MoveKind::Tunnel, MoveKind::Tunnel]);
+    assert_eq!(result.path[1].interactions, vec![Interaction::Break(vec3i!(0, 2, 0))]);
+}
+
+#[test]
+fn tunnel_refuses_unsafe_blocks() {
+    let mut unbreakable = VoxelScenario::interacting(vec![
+        vec!["#####"],
+        vec!["..#.."],
+        vec!["..#.."],
+        vec!["#####"]
+    ]);
+    unbreakable.eval_failure(vec3i!(0, 1, 0), vec3i!(4, 1, 0));
+
+    let mut liquid = VoxelScenario::interacting(vec![
+        vec!["#####"],
+        vec!["..d.."],
+        vec!["..d.."],
+        vec!["##~##"]
+    ]);
+    liquid.eval_failure(vec3i!(0, 1, 0), vec3i!(4, 1, 0));
+
+    let mut falling = VoxelScenario::interacting(vec![
+        vec!["#####"],
+        vec!["..d.."],
+        vec!["..d.."],
+        vec!["##s##"]
+    ]);
+    falling.eval_failure(vec3i!(0, 1, 0), vec3i!(4, 1, 0));
+
+    // sand that's broken itself can't fall on anyone
+    let mut broken_sand = VoxelScenario::interacting(vec![
+        vec!["#####"],
+        vec!["..d.."],
+        vec!["..s.."],
+        vec!["#####"]
+    ]);
+    broken_sand.eval_success(vec3i!(0, 1, 0), vec3i!(4, 1, 0));
+}
+
+#[test]
+fn tunnel_tool_cost() {
+    let scenario = VoxelScenario::interacting(vec![
+        vec!["###"],
+        vec![".c."],
+        vec![".c."]
+    ]);
+    let tunnel = MoveAction::of_kind(MoveKind::Tunnel, 1, vec3i!(1, 0, 0));
+    let space = scenario.calc.space();
+
+    let by_hand = space.evaluate(vec3i!(0, 1, 0), &tunnel, &Agent::new(), &PathState::default()).unwrap().cost;
+    let wrong_tool = Agent::new().with_tool(Tool::new(ToolKind::Shovel, 8.0));
+    let with_wrong_tool = space.evaluate(vec3i!(0, 1, 0), &tunnel, &wrong_tool, &PathState::default()).unwrap().cost;
+    let pickaxe = Agent::new().with_tool(Tool::new(ToolKind::Pickaxe, 8.0));
+    let with_pickaxe = space.evaluate(vec3i!(0, 1, 0), &tunnel, &pickaxe, &PathState::default()).unwrap().cost;
+
+    assert_eq!(by_hand, with_wrong_tool, "The wrong tool sped up mining");
+    assert!(with_pickaxe < by_hand, "The right tool did not speed up mining");
+    // 2 blocks, 200 ticks each by hand
+    let config = Configuration::new();
+    assert_eq!(by_hand, 1.0 + 1.0 + 2.0 * (200.0 / TICKS_PER_BLOCK + config.break_cost));
+}
+
+#[test]
+fn bridge_gap() {
+    let layers = vec![
+        vec!["##....##"],
+        vec!["........"],
+        vec!["........"]
+    ];
+    let config = Configuration {
+        allow_parkour: false,
+        allow_placing: true,
+        ..Configuration::new()
+    };
+
+    let mut scenario = VoxelScenario::with_moves(layers.clone(), default_moveset(), config)
+        .with_agent(Agent::new().with_throwaway_blocks(4));
+    let result = scenario.eval_success(vec3i!(1, 1, 0), vec3i!(6, 1, 0));
+    assert_eq!(kinds(&result), vec![MoveKind::Bridge, MoveKind::Bridge, MoveKind::Bridge, MoveKind::Bridge, MoveKind::Traverse]);
+    let placed: Vec<_> = result.path.iter().flat_map(|n| n.interactions.iter().copied()).collect();
+    assert_eq!(placed, (2..6).map(|x| Interaction::Place { pos: vec3i!(x, 0, 0), face: Face::West }).collect::<Vec<_>>(),
+               "Path did not list every placement against the block behind it");
+
+    // one block short
+    let mut short = VoxelScenario::with_moves(layers.clone(), default_moveset(), config)
+        .with_agent(Agent::new().with_throwaway_blocks(3));
+    short.eval_failure(vec3i!(1, 1, 0), vec3i!(6, 1, 0));
+
+    let disabled = Configuration {
+        allow_placing: false,
+        ..config
+    };
+    let mut disabled = VoxelScenario::with_moves(layers, default_moveset(), disabled)
+        .with_agent(Agent::new().with_throwaway_blocks(4));
+    disabled.eval_failure(vec3i!(1, 1, 0), vec3i!(6, 1, 0));
+}
+
+#[test]
+fn bridge_needs_floor() {
+    // a ladder holds the entity up, but there's nothing under it to place against
+    let mut ladder = VoxelScenario::interacting(vec![
+        vec!["..."],
+        vec!["H.."],
+        vec!["H.."]
+    ]).with_agent(Agent::new().with_throwaway_blocks(4));
+    ladder.eval_failure(vec3i!(0, 1, 0), vec3i!(2, 1, 0));
+
+    let bridge = MoveAction::of_kind(MoveKind::Bridge, 1, vec3i!(1, 0, 0));
+    let state = PathState::new(&Agent::new().with_throwaway_blocks(4));
+    let space = ladder.calc.space();
+    assert!(space.evaluate(vec3i!(0, 1, 0), &bridge, &Agent::new(), &state).is_none(), "Bridged off a ladder");
+    let placed = state.place_block();
+    assert!(space.evaluate(vec3i!(0, 1, 0), &bridge, &Agent::new(), &placed).is_some(),
+            "Did not bridge off a block placed by the move before");
+}
+
+#[test]
+fn pillar_up() {
+    let layers = vec![vec!["#"], vec!["."], vec!["."], vec!["."], vec!["."], vec!["."]];
+
+    let mut scenario = VoxelScenario::interacting(layers.clone()).with_agent(Agent::new().with_throwaway_blocks(3));
+    let result = scenario.eval_success(vec3i!(0, 1, 0), vec3i!(0, 4, 0));
+    assert_eq!(kinds(&result), vec![MoveKind::Pillar, MoveKind::Pillar, MoveKind::Pillar]);
+    let placed: Vec<_> = result.path.iter().flat_map(|n| n.interactions.iter().copied()).collect();
+    assert_eq!(placed, (1..4).map(|y| Interaction::Place { pos: vec3i!(0, y, 0), face: Face::Down }).collect::<Vec<_>>(),
+               "Path did not list every placement on top of the block below");
+
+    let mut short = VoxelScenario::interacting(layers).with_agent(Agent::new().with_throwaway_blocks(2));
+    short.eval_failure(vec3i!(0, 1, 0), vec3i!(0, 4, 0));
+}
+
+#[test]
+fn pillar_onto_ledge() {
+    let mut scenario = VoxelScenario::interacting(vec![
+        vec!["##"],
+        vec![".#"],
+        vec![".#"],
+        vec![".."],
+        vec![".."]
+    ]).with_agent(Agent::new().with_throwaway_blocks(1));
+
+    let result = scenario.eval_success(vec3i!(0, 1, 0), vec3i!(1, 3, 0));
+    assert_eq!(kinds(&result), vec![MoveKind::Pillar, MoveKind::Ascend]);
+}
+
+#[test]
+fn climb_ladder_onto_ledge() {
+    let shaft = |rung| vec![
+        vec!["##"],
+        vec![rung],
+        vec![rung],
+        vec![rung],
+        vec![".."],
+        vec![".."]
+    ];
+
+    let mut scenario = VoxelScenario::new(shaft("H#"));
+    let result = scenario.eval_success(vec3i!(0, 1, 0), vec3i!(1, 4, 0));
+    assert_eq!(kinds(&result), vec![MoveKind::Climb, MoveKind::Climb, MoveKind::Ascend],
+               "Did not climb to the top rung and step onto the ledge");
+
+    let result = scenario.eval_success(vec3i!(1, 4, 0), vec3i!(0, 1, 0));
+    assert_eq!(kinds(&result), vec![MoveKind::Descend, MoveKind::Climb, MoveKind::Climb],
+               "Did not step off the ledge onto the ladder and climb down");
+
+    let mut no_ladder = VoxelScenario::new(shaft(".#"));
+    no_ladder.eval_failure(vec3i!(0, 1, 0), vec3i!(1, 4, 0));
+}
+
+#[test]
+fn climb_ladder_needs_headroom() {
+    // nothing to hold on to past the top rung, and no room to step off of it
+    let mut scenario = VoxelScenario::new(vec![
+        vec!["###"],
+        vec!["H#."],
+        vec!["H#."],
+        vec!["H#."],
+        vec![".#."],
+        vec!["#.."]
+    ]);
+    scenario.eval_failure(vec3i!(0, 1, 0), vec3i!(1, 4, 0));
+}
+
+#[test]
+fn climb_costs() {
+    let config = Configuration::new();
+    let scenario = VoxelScenario::new(vec![
+        vec!["###"],
+        vec!["HvS"],
+        vec!["HvS"],
+        vec!["HvS"]
+    ]);
+    let up = MoveAction::of_kind(MoveKind::Climb, 1, vec3i!(0, 1, 0));
+    let cost = |x| scenario.calc.space()
+        .evaluate(vec3i!(x, 1, 0), &up, &Agent::new(), &PathState::default())
+        .map(|result| result.cost);
+
+    // climbables are as costly to be in as water
+    let body = config.hazard.multiplier_for(Some(BlockClass::Climbable(Climb::Ladder))).unwrap();
+    assert_eq!(cost(0), Some(config.climb_cost.ladder + body));
+    assert_eq!(cost(1), Some(config.climb_cost.vine + body));
+    assert_eq!(cost(2), Some(config.climb_cost.scaffolding + body));
+}
+
+#[test]
+fn climb_scaffolding() {
+    let mut scenario = VoxelScenario::new(vec![
+        vec!["##"],
+        vec!["S."],
+        vec!["S."],
+        vec![".."],
+        vec![".."]
+    ]);
+
+    // scaffolding can be stood on and climbed down through from the top
+    let result = scenario.eval_success(vec3i!(0, 3, 0), vec3i!(0, 1, 0));
+    assert_eq!(kinds(&result), vec![MoveKind::Climb, MoveKind::Climb]);
+    let result = scenario.eval_success(vec3i!(1, 1, 0), vec3i!(0, 3, 0));
+    assert_eq!(kinds(&result).last(), Some(&MoveKind::Climb), "Did not climb up the scaffolding");
+}
+
+#[test]
+fn swim_across_pool() {
+    let config = Configuration {
+        allow_parkour: false,
+        ..Configuration::new()
+    };
+    let mut scenario = VoxelScenario::with_moves(vec![
+        vec!["#####"],
+        vec!["#~~~#"],
+        vec!["#~~~#"],
+        vec!["....."],
+        vec!["....."]
+    ], default_moveset(), config);
+
+    let result = scenario.eval_success(vec3i!(0, 3, 0), vec3i!(4, 3, 0));
+    assert_eq!(kinds(&result), vec![MoveKind::Descend, MoveKind::Swim, MoveKind::Swim, MoveKind::Ascend],
+               "Did not swim along the surface");
+}
+
+#[test]
+fn swim_costs() {
+    let config = Configuration::new();
+    let scenario = VoxelScenario::new(vec![
+        vec!["###"],
+        vec!["~~f"],
+        vec!["~~."],
+        vec!["..."]
+    ]);
+    let agent = Agent::new();
+    let state = PathState::new(&agent);
+    let swim = MoveAction::of_kind(MoveKind::Swim, 1, vec3i!(1, 0, 0));
+    let evaluate = |from| scenario.calc.space().evaluate(from, &swim, &agent, &state).unwrap();
+
+    let surface = evaluate(vec3i!(0, 2, 0));
+    assert_eq!(surface.cost, config.swim_ticks.surface / TICKS_PER_BLOCK);
+    assert_eq!(surface.state, None, "Swimming on the surface used up air");
+
+    let diving = evaluate(vec3i!(0, 1, 0));
+    assert_eq!(diving.cost, config.swim_ticks.diving / TICKS_PER_BLOCK);
+    assert_eq!(diving.state.map(|state| state.air), Some(agent.air_supply - config.swim_ticks.diving as u32),
+               "Diving did not use up air");
+
+    let current = evaluate(vec3i!(1, 1, 0));
+    assert_eq!(current.cost, (config.swim_ticks.surface + config.swim_ticks.current) / TICKS_PER_BLOCK,
+               "Flowing water did not push back");
+
+    let into_air = scenario.calc.space().evaluate(vec3i!(1, 2, 0), &swim, &agent, &state);
+    assert!(into_air.is_none(), "Swam out of the water");
+
+    let lava = VoxelScenario::new(vec![vec!["##"], vec!["LL"], vec![".."]]);
+    let resistant = Agent::new().with_fire_resistance(true);
+    let through_lava = lava.calc.space().evaluate(vec3i!(0, 1, 0), &swim, &resistant, &state).unwrap();
+    let hazard = config.hazard.dangerous as f64 / 10.0 - config.hazard.non_solid as f64 / 10.0;
+    assert!((through_lava.cost - (config.swim_ticks.surface / TICKS_PER_BLOCK + hazard)).abs() < 1e-9,
+            "Lava was swum at the cost of water");
+}
+
+#[test]
+fn swim_breath() {
+    // the ceiling forces the entity to dive under it for 8 blocks, 88 ticks
+    let layers = vec![
+        vec!["##########"],
+        vec!["#~~~~~~~~#"],
+        vec!["#~~~~~~~~#"],
+        vec!["..######.."],
+        vec!["..######.."],
+        vec!["..######.."]
+    ];
+    let mut scenario = VoxelScenario::new(layers.clone());
+    let result = scenario.eval_success(vec3i!(1, 2, 0), vec3i!(8, 2, 0));
+    assert!(kinds(&result).contains(&MoveKind::Swim), "Did not dive under the ceiling");
+
+    let mut enough = VoxelScenario::new(layers.clone()).with_agent(Agent::new().with_air_supply(90));
+    enough.eval_success(vec3i!(1, 2, 0), vec3i!(8, 2, 0));
+
+    let mut drowning = VoxelScenario::new(layers).with_agent(Agent::new().with_air_supply(80));
+    drowning.eval_failure(vec3i!(1, 2, 0), vec3i!(8, 2, 0));
+}
+
+#[test]
+fn breath_follows_move_time() {
+    let layers = vec![
+        vec!["###"],
+        vec!["#~~"],
+        vec!["#~~"],
+        vec!["..."]
+    ];
+    let agent = Agent::new();
+    let state = PathState::new(&agent);
+    let descend = MoveAction::of_kind(MoveKind::Descend, 1, vec3i!(1, -1, 0));
+    let air_after = |non_solid| {
+        let mut config = Configuration::new();
+        config.hazard.non_solid = non_solid;
+        let scenario = VoxelScenario::with_moves(layers.clone(), default_moveset(), config);
+        let result = scenario.calc.space().evaluate(vec3i!(0, 2, 0), &descend, &agent, &state).unwrap();
+        result.state.map(|state| state.air)
+    };
+
+    // what the water costs has no bearing on how long the move takes
+    let expected = agent.air_supply - (descend.base_cost() * TICKS_PER_BLOCK).round() as u32;
+    assert_eq!(air_after(21), Some(expected), "Diving in used up air for its cost rather than its time");
+    assert_eq!(air_after(210), Some(expected), "Diving in used up air for its cost rather than its time");
+}
+
+#[test]
+fn lava_needs_fire_resistance() {
+    let config = Configuration {
+        allow_parkour: false,
+        ..Configuration::new()
+    };
+    let layers = vec![
+        vec!["#####"],
+        vec!["#LLL#"],
+        vec!["....."],
+        vec!["....."]
+    ];
+
+    let mut scenario = VoxelScenario::with_moves(layers.clone(), default_moveset(), config);
+    scenario.eval_failure(vec3i!(0, 2, 0), vec3i!(4, 2, 0));
+
+    let mut resistant = VoxelScenario::with_moves(layers, default_moveset(), config)
+        .with_agent(Agent::new().with_fire_resistance(true));
+    resistant.eval_success(vec3i!(0, 2, 0), vec3i!(4, 2, 0));
+}
+
+/// A corridor with a doorway at `x = 2`, the lower half of the door being `lower` and the upper
+/// half `upper`.
+fn doorway(lower: &str, upper: &str) -> VoxelScenario {
+    VoxelScenario::new(vec![
+        vec!["#####"],
+        vec![lower],
+        vec![upper],
+        vec!["#####"]
+    ])
+}
+
+#[test]
+fn door_opened_on_the_way() {
+    let mut scenario = doorway("..D..", "..U..");
+    let result = scenario.eval_success(vec3i!(0, 1, 0), vec3i!(4, 1, 0));
+    assert_eq!(kinds(&result), vec![MoveKind::Traverse; 4]);
+
+    let marked: Vec<_> = result.path.iter().filter(|n| n.requires_interaction()).collect();
+    assert_eq!(marked.len(), 1, "Door was not marked exactly once");
+    assert_eq!(marked[0].action.pos, vec3i!(2, 1, 0));
+    assert_eq!(marked[0].interactions, vec![Interaction::Open(vec3i!(2, 1, 0))],
+               "Door was not opened from its lower half");
+
+    let config = Configuration::new();
+    let traverse = MoveAction::new(1, vec3i!(1, 0, 0));
+    let cost = scenario.calc.space().evaluate(vec3i!(1, 1, 0), &traverse, &Agent::new(), &PathState::default())
+        .map(|result| result.cost);
+    assert_eq!(cost, Some(1.0 + 1.0 + config.open_cost), "Opening was not priced");
+}
+
+#[test]
+fn open_door() {
+    let mut scenario = doorway("..O..", "..O..");
+    let result = scenario.eval_success(vec3i!(0, 1, 0), vec3i!(4, 1, 0));
+    assert!(result.path.iter().all(|n| !n.requires_interaction()), "Opened a door that was already open");
+}
+
+#[test]
+fn iron_door_blocks() {
+    let mut scenario = doorway("..I..", "..I..");
+    scenario.eval_failure(vec3i!(0, 1, 0), vec3i!(4, 1, 0));
+}
+
+#[test]
+fn trapdoor_and_fence_gate() {
+    // the closed trapdoor covers a hole, the fence gate needs opening
+    let config = Configuration {
+        allow_parkour: false,
+        ..Configuration::new()
+    };
+    let mut scenario = VoxelScenario::with_moves(vec![
+        vec!["##.##"],
+        vec!["##T##"],
+        vec!["....G"],
+        vec!["....."],
+        vec!["#####"]
+    ], default_moveset(), config);
+
+    let result = scenario.eval_success(vec3i!(0, 2, 0), vec3i!(4, 2, 0));
+    let opened: Vec<_> = result.path.iter().flat_map(|n| n.interactions.clone()).collect();
+    assert_eq!(opened, vec![Interaction::Open(vec3i!(4, 2, 0))], "Did not walk over the trapdoor and through the gate");
+}
+
+#[test]
+fn hitbox_clearance() {
+    // a two blocks wide corridor, with `ceiling` being the layer three blocks above the floor and
+    // `crawl` the one right above it
+    let corridor = |crawl, ceiling| vec![
+        vec!["#####", "#####"],
+        vec![".....", "....."],
+        vec![crawl, crawl],
+        vec![ceiling, ceiling],
+        vec!["#####", "#####"]
+    ];
+    let player = Agent::new();
+    let large = Agent::new().with_hitbox(Hitbox::new(2.0, 2.9));
+    let baby = Agent::new().with_hitbox(Hitbox::new(0.3, 0.9));
+
+    let mut open = VoxelScenario::new(corridor(".....", ".....")).with_agent(large);
+    open.eval_success(vec3i!(0, 1, 0), vec3i!(3, 1, 0));
+
+    let mut low = VoxelScenario::new(corridor(".....", "..#..")).with_agent(large);
+    low.eval_failure(vec3i!(0, 1, 0), vec3i!(3, 1, 0));
+    let mut low = VoxelScenario::new(corridor(".....", "..#..")).with_agent(player);
+    low.eval_success(vec3i!(0, 1, 0), vec3i!(3, 1, 0));
+
+    let mut crawl_space = VoxelScenario::new(corridor("..#..", ".....")).with_agent(player);
+    crawl_space.eval_failure(vec3i!(0, 1, 0), vec3i!(3, 1, 0));
+    let mut crawl_space = VoxelScenario::new(corridor("..#..", ".....")).with_agent(baby);
+    crawl_space.eval_success(vec3i!(0, 1, 0), vec3i!(3, 1, 0));
+}
+
+/// Returns the kinds of every move along a path, excluding the root.
+fn kinds(result: &PathResult<Vector3i>) -> Vec<MoveKind> {
+    result.path.iter()
+        .filter_map(|n| n.action.move_action.map(|a| a.kind))
+        .collect()
+}
+
+/// Builds a `VoxelSpace` out of horizontal layers, from the bottom up. Each layer is a list of rows
+/// along Z, with each character being a block along X. \
+/// `#`: Stone \
+/// `~`: Water \
+/// `h`: Hay bale \
+/// `d`: Dirt \
+/// `s`: Sand \
+/// `c`: Cobblestone \
+/// `H`: Ladder \
+/// `v`: Vine \
+/// `S`: Scaffolding \
+/// `L`: Lava \
+/// `f`: Flowing water \
+/// `D`: Closed door, lower half \
+/// `U`: Closed door, upper half \
+/// `O`: Open door \
+/// `I`: Closed iron door \
+/// `T`: Closed trapdoor \
+/// `G`: Closed fence gate \
+/// `.`: Air
+struct VoxelScenario {
+    calc: PathCalculator<Vector3i, VoxelSpace>
+}
+
+impl VoxelScenario {
+    fn new(layers: Vec<Vec<&str>>) -> VoxelScenario {
+        Self::with_moves(layers, default_moveset(), Configuration::new())
+    }
+
+    /// Creates a scenario where blocks may be broken and placed.
+    fn interacting(layers: Vec<Vec<&str>>) -> VoxelScenario {
+        let config = Configuration {
+            allow_breaking: true,
+            allow_placing: true,
+            ..Configuration::new()
+        };
+        Self::with_moves(layers, default_moveset(), config)
+    }
+
+    fn with_moves(layers: Vec<Vec<&str>>, moves: Moveset<Vector3i>, config: Configuration) -> VoxelScenario {
+        let registry = BlockRegistry::from_json(BLOCKS).unwrap();
+        let space = VoxelSpace::new(config, Arc::new(registry));
+
+        for (y, layer) in layers.iter().enumerate() {
+            for (z, row) in layer.iter().enumerate() {
+                for (x, c) in row.chars().enumerate() {
+                    let pos = vec3i!(x as i32, y as i32, z as i32);
+                    if !space.is_loaded(section_pos_of(pos)) {
+                        space.load_section(section_pos_of(pos), ChunkSection::new());
+                    }
+
+                    let state = match c {
+                        '#' => 1,
+                        '~' => 2,
+                        'h' => 3,
+                        'd' => 4,
+                        's' => 5,
+                        'c' => 6,
+                        'H' => 7,
+                        'v' => 8,
+                        'S' => 9,
+                        'L' => 10,
+                        'f' => 11,
+                        'D' => 12,
+                        'U' => 13,
+                        'O' => 14,
+                        'I' => 15,
+                        'T' => 16,
+                        'G' => 17,
+                        _ => 0
+                    };
+                    space.set_block(pos, state).unwrap();
+                }
+            }
+        }
+
+        VoxelScenario {
+            calc: PathCalculator::new(moves, config, Box::new(space))
+        }
+    }
+
+    /// A pillar at `x = 0` to stand on at `y = height`, next to a drop down to `y = 1`. The block
+    /// at the bottom of the drop is `landing`, water is put on top of a stone floor.
+    fn drop(height: usize, landing: char, config: Configuration) -> VoxelScenario {
+        let (floor, bottom) = match landing {
+            '~' => ("##".to_string(), "#~".to_string()),
+            _ => (format!("#{}", landing), "#.".to_string())
+        };
+        let mut layers = vec![floor, bottom];
+        layers.resize(height, "#.".to_string());
+        layers.resize(height + 3, "..".to_string());
+
+        Self::with_moves(layers.iter().map(|l| vec![l.as_str()]).collect(), default_moveset(), config)
+    }
+
+    /// A `gap` blocks wide hole in the floor, with the landing side `rise` blocks higher than the
+    /// take-off side at `y = 1`. The take-off side is two blocks long.
+    fn gap(gap: i32, rise: i32, config: Configuration) -> VoxelScenario {
+        let width = gap as usize + 4;
+        let row = |floor: bool, landing: bool| (0..width)
+            .map(|x| if (floor && x < 2) || (landing && x >= 2 + gap as usize) { '#' } else { '.' })
+            .collect::<String>();
+
+        let mut layers = vec![row(true, true)];
+        layers.extend((0..rise).map(|_| row(false, true)));
+        layers.extend((0..4).map(|_| row(false, false)));
+
+        Self::with_moves(layers.iter().map(|l| vec![l.as_str()]).collect(), default_moveset(), config)
+    }
+
+    fn with_agent(mut self, agent: Agent) -> VoxelScenario {
+        self.calc.set_agent(agent);
+        self
+    }
+
+    fn eval_success(&mut self, start: Vector3i, end: Vector3i) -> PathResult<Vector3i> {
+        let out = self.calc.calculate(start, end);
+        assert!(out.is_ok(), "Pathfinding failed with error: {}", out.unwrap_err());
+        let result = out.unwrap();
+        assert_eq!(result.status, PathStatus::Complete, "Pathfinder did not complete the path");
+        assert_eq!(result.end(), Some(end), "Pathfinder did not reach the end successfully");
+        result
+    }
+
+    fn eval_failure(&mut self, start: Vector3i, end: Vector3i) {
+        let out = self.calc.calculate(start, end);
+        assert!(out.is_ok(), "Pathfinding failed with error: {}", out.unwrap_err());
+        let result = out.unwrap();
+        // an impossible path can still be partial, as long as it stops short of the end
+        assert!(result.status != PathStatus::Complete && result.end() != Some(end),
+                "Pathfinder calculated an impossible path: {:?}", result.path.iter().map(|n| n.action.pos).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,38 @@
+use crate::pathing::agent::{Agent, Hitbox, Tool};
+use crate::pathing::block::{BlockClass, BlockInfo, ToolKind};
+
+#[test]
+fn break_ticks() {
+    let dirt = BlockInfo::new("minecraft:dirt", BlockClass::Solid).with_hardness(0.5, Some(ToolKind::Shovel));
+    let mut cobblestone = BlockInfo::new("minecraft:cobblestone", BlockClass::Solid)
+        .with_hardness(2.0, Some(ToolKind::Pickaxe));
+    cobblestone.requires_tool = true;
+    let flower = BlockInfo::new("minecraft:poppy", BlockClass::Air).with_hardness(0.0, None);
+    let snow = BlockInfo::new("minecraft:snow_block", BlockClass::Solid).with_hardness(0.2, Some(ToolKind::Shovel));
+    let bedrock = BlockInfo::new("minecraft:bedrock", BlockClass::Solid);
+
+    let hand = Agent::new();
+    let wooden_shovel = Agent::new().with_tool(Tool::new(ToolKind::Shovel, 2.0));
+    let wooden_pickaxe = Agent::new().with_tool(Tool::new(ToolKind::Pickaxe, 2.0));
+    let golden_shovel = Agent::new().with_tool(Tool::new(ToolKind::Shovel, 12.0));
+
+    assert_eq!(hand.break_ticks(&dirt), Some(15.0));
+    assert_eq!(wooden_shovel.break_ticks(&dirt), Some(8.0), "Tool did not speed up mining");
+    assert_eq!(wooden_pickaxe.break_ticks(&dirt), Some(15.0), "The wrong tool sped up mining");
+    assert_eq!(hand.break_ticks(&cobblestone), Some(200.0), "Mining without the required tool was not slowed");
+    assert_eq!(wooden_pickaxe.break_ticks(&cobblestone), Some(30.0));
+    assert_eq!(hand.break_ticks(&flower), Some(0.0), "Block without hardness was not broken instantly");
+    assert_eq!(golden_shovel.break_ticks(&dirt), Some(2.0));
+    assert_eq!(golden_shovel.break_ticks(&snow), Some(0.0), "Fast enough tool did not break instantly");
+    assert_eq!(golden_shovel.break_ticks(&bedrock), None, "Unbreakable block was breakable");
+}
+
+#[test]
+fn hitbox_blocks() {
+    assert_eq!(Hitbox::PLAYER.width_blocks(), 1);
+    assert_eq!(Hitbox::PLAYER.height_blocks(), 2);
+    assert_eq!(Hitbox::new(1.0, 1.0).width_blocks(), 1, "Exact fit took up an extra block");
+    assert_eq!(Hitbox::new(1.4, 0.9).width_blocks(), 2);
+    assert_eq!(Hitbox::new(1.4, 0.9).height_blocks(), 1);
+    assert_eq!(Agent::new().hitbox, Hitbox::PLAYER, "Agents were not player-sized by default");
+}
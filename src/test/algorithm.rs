@@ -1,10 +1,17 @@
-use crate::config::Configuration;
-use crate::pathing::action::{moveset_2d_cardinal, MoveAction, Moveset2D, Moveset, SpatialAction};
-use crate::pathing::algorithm::PathCalculator;
+use crate::config::{Configuration, CornerCutting};
+use crate::pathing::action::{moveset_2d_cardinal, moveset_2d_diagonal, Moveset2D, Moveset, SpatialAction};
+use crate::pathing::algorithm::{CancelToken, PathCalculator};
 use crate::pathing::math::Vector2i;
-use crate::pathing::world::FlatSpace;
+use crate::pathing::algorithm::GraphPosition;
+use crate::pathing::world::{FlatSpace, Space};
 use crate::vec2i;
-use crate::pathing::data::PathNode;
+use crate::pathing::data::{Node, PathNode, PathState, PathStatus};
+use dashmap::DashMap;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::time::{Duration, Instant};
 
 #[test]
 fn scenario_from_str() {
@@ -200,6 +207,322 @@ fn pathfinder_maze_complex() {
     ]);
 }
 
+#[test]
+fn pathfinder_diagonal() {
+    let mut scenario = PathfindingScenario2D::new(vec![
+        "O____",
+        "_____",
+        "_____",
+        "_____",
+        "____G"
+    ], moveset_2d_diagonal());
+
+    scenario.eval(vec2i!(0, 0), vec2i!(4, 4), vec![
+        "O    ",
+        " O   ",
+        "  O  ",
+        "   O ",
+        "    O"
+    ]);
+}
+
+#[test]
+fn pathfinder_diagonal_cost() {
+    let mut scenario = PathfindingScenario2D::new(vec![
+        "O___G"
+    ], moveset_2d_diagonal());
+
+    // zigzagging diagonally would be as many nodes, but costs more
+    scenario.eval(vec2i!(0, 0), vec2i!(4, 0), vec![
+        "OOOOO"
+    ]);
+    assert_eq!(Moveset2D::DownRight.of().base_cost(), std::f64::consts::SQRT_2, "Diagonal cost was not scaled");
+}
+
+#[test]
+fn pathfinder_corner_cutting() {
+    let one_side = vec![
+        "OX",
+        "_G"
+    ];
+    let both_sides = vec![
+        "OX",
+        "XG"
+    ];
+    let config = |corner_cutting| Configuration { corner_cutting, ..Configuration::new() };
+
+    let mut forbid = PathfindingScenario2D::with_config(one_side.clone(), moveset_2d_diagonal(), config(CornerCutting::Forbid));
+    forbid.eval(vec2i!(0, 0), vec2i!(1, 1), vec![
+        "O ",
+        "OO"
+    ]);
+    let mut forbid = PathfindingScenario2D::with_config(both_sides.clone(), moveset_2d_diagonal(), config(CornerCutting::Forbid));
+    forbid.eval_failure(vec2i!(0, 0), vec2i!(1, 1));
+
+    let mut one = PathfindingScenario2D::with_config(one_side, moveset_2d_diagonal(), config(CornerCutting::AllowOneSide));
+    one.eval(vec2i!(0, 0), vec2i!(1, 1), vec![
+        "O ",
+        " O"
+    ]);
+    let mut one = PathfindingScenario2D::with_config(both_sides.clone(), moveset_2d_diagonal(), config(CornerCutting::AllowOneSide));
+    one.eval_failure(vec2i!(0, 0), vec2i!(1, 1));
+
+    let mut allow = PathfindingScenario2D::with_config(both_sides, moveset_2d_diagonal(), config(CornerCutting::Allow));
+    allow.eval_success(vec2i!(0, 0), vec2i!(1, 1));
+}
+
+#[test]
+fn pathfinder_timeout_partial() {
+    let mut config = Configuration::new();
+    config.timeout = Duration::from_millis(50);
+    let goal = vec2i!(8, 0);
+    let space = Box::new(EndlessSpace { wall: goal, config });
+    let mut calc = PathCalculator::new(moveset_2d_cardinal(), config, space);
+
+    let start_time = Instant::now();
+    let out = calc.calculate(vec2i!(0, 0), goal);
+    assert!(start_time.elapsed() < Duration::from_secs(5), "Pathfinder did not honor the timeout");
+    assert!(out.is_ok(), "Pathfinding failed with error: {}", out.unwrap_err());
+
+    let result = out.unwrap();
+    assert_eq!(result.status, PathStatus::Partial, "Unreachable goal in an endless space was not partial");
+    assert_eq!(result.path.first().map(|n| n.action.pos), Some(vec2i!(0, 0)), "Partial path did not begin at the start");
+    let end = result.end();
+    assert!(end.is_some(), "Partial path was empty");
+    assert_eq!(end.unwrap().distance_to(&goal), 1.0, "Partial path did not end next to the goal");
+}
+
+#[test]
+fn pathfinder_walled_off_partial() {
+    let mut scenario = PathfindingScenario2D::new(vec![
+        "O__X__",
+        "___X_G",
+        "___X__"
+    ], moveset_2d_cardinal());
+
+    let out = scenario.calc.calculate(vec2i!(0, 0), vec2i!(5, 1));
+    assert!(out.is_ok(), "Pathfinding failed with error: {}", out.unwrap_err());
+    let result = out.unwrap();
+    assert_eq!(result.status, PathStatus::Partial, "Walled off goal was not partial once out of nodes");
+    assert_eq!(result.path.first().map(|n| n.action.pos), Some(vec2i!(0, 0)), "Partial path did not begin at the start");
+    assert_eq!(result.end(), Some(vec2i!(2, 1)), "Partial path did not end next to the wall");
+    assert_eq!(result.path.len(), 4, "Partial path was not the shortest to its end");
+
+    // boxed in, nothing gets any closer than the start
+    let mut boxed = PathfindingScenario2D::new(vec![
+        "OX_",
+        "X__",
+        "__G"
+    ], moveset_2d_cardinal());
+    let out = boxed.calc.calculate(vec2i!(0, 0), vec2i!(2, 2));
+    assert_eq!(out.map(|r| r.status).ok(), Some(PathStatus::Failed), "Boxed in start did not fail");
+}
+
+#[test]
+fn pathfinder_timeout_reset() {
+    let mut config = Configuration::new();
+    config.timeout = Duration::from_millis(20);
+    let space = Box::new(EndlessSpace { wall: vec2i!(10, 10), config });
+    let mut calc = PathCalculator::new(moveset_2d_cardinal(), config, space);
+
+    let timed_out = calc.calculate(vec2i!(0, 0), vec2i!(10, 10));
+    assert_eq!(timed_out.map(|r| r.status).ok(), Some(PathStatus::Partial));
+
+    // a reachable goal after a partial search still completes
+    let out = calc.calculate(vec2i!(0, 0), vec2i!(3, 0));
+    assert!(out.is_ok(), "Pathfinding failed with error: {}", out.unwrap_err());
+    let result = out.unwrap();
+    assert!(result.is_complete(), "Pathfinder did not complete after a partial search");
+    assert_eq!(result.path.len(), 4);
+    assert!(calc.expanded() < 10, "Expanded nodes of the last calculation were counted too");
+}
+
+#[test]
+fn pathfinder_cancelled() {
+    let mut config = Configuration::new();
+    config.timeout = Duration::from_secs(60);
+    let space = Box::new(EndlessSpace { wall: vec2i!(10, 10), config });
+    let mut calc = PathCalculator::new(moveset_2d_cardinal(), config, space);
+
+    let cancel = CancelToken::new();
+    cancel.cancel();
+    calc.set_cancel_token(cancel);
+    let start_time = Instant::now();
+    let out = calc.calculate(vec2i!(0, 0), vec2i!(10, 10));
+    assert!(start_time.elapsed() < Duration::from_secs(5), "Pathfinder did not honor the cancellation");
+    assert_eq!(out.map(|r| r.status).ok(), Some(PathStatus::Cancelled), "Cancelled search was not reported as such");
+
+    // a fresh token lets the calculator be used again
+    calc.set_cancel_token(CancelToken::new());
+    let out = calc.calculate(vec2i!(0, 0), vec2i!(3, 0));
+    assert!(out.is_ok_and(|r| r.is_complete()), "Pathfinder did not complete with a fresh token");
+}
+
+#[test]
+fn pathfinder_expands_once() {
+    // the goal is walled off, so every reachable cell has to be expanded before giving up
+    let environment = vec![
+        "O____",
+        "_____",
+        "__X__",
+        "____X",
+        "___XG"
+    ];
+    let reachable = environment.iter()
+        .map(|row| row.chars().filter(|c| *c != 'X' && *c != 'G').count())
+        .sum::<usize>();
+    let mut scenario = PathfindingScenario2D::new(environment, moveset_2d_cardinal());
+
+    let out = scenario.calc.calculate(vec2i!(0, 0), vec2i!(4, 4));
+    assert_eq!(out.map(|r| r.status).ok(), Some(PathStatus::Partial), "Pathfinder reached a walled off goal");
+    // each cell can be reached by several moves, but must only be expanded once
+    assert_eq!(scenario.calc.expanded(), reachable, "Pathfinder expanded a cell more than once");
+}
+
+#[test]
+fn against_reference_maze_128() {
+    against_reference_maze(128, 1);
+}
+
+#[test]
+fn against_reference_maze_512() {
+    against_reference_maze(512, 2);
+}
+
+/// Compares the arena-backed calculator against a straightforward `DashMap`-backed A* on a large
+/// random maze. Both are optimal, so their paths have to be just as long.
+fn against_reference_maze(size: usize, seed: u64) {
+    let mut config = Configuration::new();
+    config.timeout = Duration::from_secs(120);
+    let maze = random_maze(size, seed);
+    let start = vec2i!(0, 0);
+    let end = vec2i!(size as i32 - 1, size as i32 - 1);
+    let moves = moveset_2d_cardinal();
+
+    let reference_space = FlatSpace::new(maze.clone(), config);
+    let reference = reference_astar(&reference_space, &moves, config, start, end);
+
+    let mut calc = PathCalculator::new(moves, config, Box::new(FlatSpace::new(maze, config)));
+    let out = calc.calculate(start, end);
+    assert!(out.is_ok(), "Pathfinding failed with error: {}", out.unwrap_err());
+    let result = out.unwrap();
+
+    assert_eq!(result.is_complete(), reference.is_some(), "Calculators disagreed on whether a path exists");
+    if let Some(reference_len) = reference {
+        assert_eq!(result.path.len(), reference_len, "Calculators found paths of different lengths");
+    }
+}
+
+#[test]
+#[ignore = "benchmark, run with --ignored --nocapture"]
+fn benchmark_reference_maze_512() {
+    let mut config = Configuration::new();
+    config.timeout = Duration::from_secs(120);
+    let maze = random_maze(512, 2);
+    let start = vec2i!(0, 0);
+    let end = vec2i!(511, 511);
+    let moves = moveset_2d_cardinal();
+
+    let reference_space = FlatSpace::new(maze.clone(), config);
+    let start_time = Instant::now();
+    let reference = reference_astar(&reference_space, &moves, config, start, end);
+    let reference_time = start_time.elapsed();
+
+    let mut calc = PathCalculator::new(moves, config, Box::new(FlatSpace::new(maze, config)));
+    let start_time = Instant::now();
+    let result = calc.calculate(start, end).unwrap();
+    let arena_time = start_time.elapsed();
+
+    assert_eq!(Some(result.path.len()), reference, "Calculators found different paths");
+    println!("512x512 maze: reference took {} ms, arena took {} ms", reference_time.as_millis(), arena_time.as_millis());
+}
+
+/// Generates a maze of random walls. The start and end corners, and a winding corridor between
+/// them, are always left open, so every maze is solvable.
+fn random_maze(size: usize, seed: u64) -> Vec<&'static str> {
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let mut rows: Vec<Vec<u8>> = (0..size)
+        .map(|_| (0..size).map(|_| if rng.random_range(0..100) < 30 { b'X' } else { b'_' }).collect())
+        .collect();
+
+    // carve a staircase from corner to corner
+    let (mut x, mut y) = (0, 0);
+    while x < size - 1 || y < size - 1 {
+        rows[y][x] = b'_';
+        if y == size - 1 || (x < size - 1 && rng.random_bool(0.5)) { x += 1 } else { y += 1 }
+    }
+    rows[y][x] = b'_';
+
+    rows.into_iter()
+        .map(|row| &*String::from_utf8(row).unwrap().leak())
+        .collect()
+}
+
+/// A plain A* over a `DashMap` node store and a `std` heap with lazy deletion, the way nodes were
+/// stored before the arena. Returns the amount of nodes in the path, if one was found.
+fn reference_astar(space: &FlatSpace, moves: &Moveset<Vector2i>, config: Configuration,
+                   start: Vector2i, end: Vector2i) -> Option<usize> {
+    let nodes: DashMap<Vector2i, (f64, Option<Vector2i>)> = DashMap::new();
+    let mut open = BinaryHeap::new();
+    nodes.insert(start, (0.0, None));
+    open.push(Reverse(Node {
+        g_cost: 0.0,
+        h_cost: start.distance_to(&end),
+        action: SpatialAction::new_root(start),
+        parent: None,
+        heap_idx: None,
+        state: PathState::default(),
+        interactions: None
+    }));
+
+    while let Some(Reverse(current)) = open.pop() {
+        let pos = current.action.pos;
+        // skip entries that were improved after being pushed
+        if nodes.get(&pos).is_some_and(|n| n.0 < current.g_cost) { continue }
+        if pos == end {
+            let mut len = 1;
+            let mut next = nodes.get(&pos).and_then(|n| n.1);
+            while let Some(parent) = next {
+                len += 1;
+                next = nodes.get(&parent).and_then(|n| n.1);
+            }
+            return Some(len);
+        }
+
+        for action in moves.iter() {
+            let neighbor = pos + action.offset;
+            let g_cost = current.g_cost + action.base_cost() + space.material_cost(neighbor);
+            let known = nodes.get(&neighbor).map(|n| n.0).unwrap_or(config.cost_inf);
+            if known - g_cost > 0.01 {
+                nodes.insert(neighbor, (g_cost, Some(pos)));
+                open.push(Reverse(Node {
+                    g_cost,
+                    h_cost: neighbor.distance_to(&end),
+                    action: SpatialAction::new(neighbor, *action),
+                    parent: None,
+                    heap_idx: None,
+                    state: PathState::default(),
+                    interactions: None
+                }));
+            }
+        }
+    }
+
+    None
+}
+
+/// An endless, empty plane with a single impassable cell.
+pub(super) struct EndlessSpace {
+    pub(super) wall: Vector2i,
+    pub(super) config: Configuration
+}
+
+impl Space<Vector2i> for EndlessSpace {
+    fn material_cost(&self, pos: Vector2i) -> f64 {
+        if pos == self.wall { self.config.cost_inf } else { 1.0 }
+    }
+}
+
 struct PathfindingScenario2D {
     calc: PathCalculator<Vector2i, FlatSpace>,
     moveset: Moveset<Vector2i>,
@@ -208,7 +531,10 @@ struct PathfindingScenario2D {
 
 impl PathfindingScenario2D {
     fn new(environment: Vec<&'static str>, moveset: Moveset<Vector2i>) -> PathfindingScenario2D {
-        let config = Configuration::new();
+        Self::with_config(environment, moveset, Configuration::new())
+    }
+
+    fn with_config(environment: Vec<&'static str>, moveset: Moveset<Vector2i>, config: Configuration) -> PathfindingScenario2D {
         let space = Box::new(FlatSpace::new(environment.clone(), config));
         let moves = moveset;
         PathfindingScenario2D {
@@ -222,8 +548,10 @@ impl PathfindingScenario2D {
         // calculate
         let out = self.calc.calculate(start, end);
         assert!(out.is_ok(), "Pathfinding failed with error: {}", out.unwrap_err());
-        let path = out.unwrap();
-        assert!(path.len() > 0, "Pathfinder returned an empty path");
+        let result = out.unwrap();
+        assert_eq!(result.status, PathStatus::Complete, "Pathfinder did not complete the path");
+        let path = result.path;
+        assert!(!path.is_empty(), "Pathfinder returned an empty path");
 
         // now compare paths
         let target_path: Vec<PathNode<Vector2i>> = self.to_positions(start, end, follow);
@@ -244,9 +572,7 @@ impl PathfindingScenario2D {
                        self.draw_path(&path)
             );
         }
-        // clean up
         println!("---SUCCESS---\n{}", self.draw_path(&target_path));
-        self.calc.reset()
     }
 
     fn to_positions(&self, start: Vector2i, end: Vector2i, follow: Vec<&'static str>) -> Vec<PathNode<Vector2i>> {
@@ -277,16 +603,14 @@ impl PathfindingScenario2D {
         out_path
     }
 
-    fn draw_path(&self, path: &Vec<PathNode<Vector2i>>) -> String {
+    fn draw_path(&self, path: &[PathNode<Vector2i>]) -> String {
         let mut environ_str: Vec<String> = self.environment.iter()
             .map(|row| row.to_string()).collect();
 
         // iterate through the positions
         for i in 0..path.len() {
             let pos = path[i].action.pos;
-            let c;
-
-            c = match path[i].action.move_action {
+            let c = match path[i].action.move_action {
                 Some(came_from) => {
                     if let Some(going_to_pn) = path.get(i + 1) &&
                         let Some(going_to) = going_to_pn.action.move_action {
@@ -350,18 +674,21 @@ impl PathfindingScenario2D {
     fn eval_success(&mut self, start: Vector2i, end: Vector2i) {
         let out = self.calc.calculate(start, end);
         assert!(out.is_ok(), "Pathfinding failed with error: {}", out.unwrap_err());
-        let path = out.unwrap();
-        assert!(path.len() > 0, "Pathfinder returned an empty path");
+        let result = out.unwrap();
+        assert_eq!(result.status, PathStatus::Complete, "Pathfinder did not complete the path");
+        let path = result.path;
+        assert!(!path.is_empty(), "Pathfinder returned an empty path");
         assert_eq!(path.last().unwrap().action.pos, end, "Pathfinder did not reach the end successfully");
 
         println!("---SUCCESS---\n{}", self.draw_path(&path));
-        self.calc.reset()
     }
 
     fn eval_failure(&mut self, start: Vector2i, end: Vector2i) {
         let out = self.calc.calculate(start, end);
         assert!(out.is_ok(), "Pathfinding failed with error: {}", out.unwrap_err());
-        assert_eq!(out.unwrap().len(), 0, "Pathfinder calculated an impossible path");
-        self.calc.reset();
+        let result = out.unwrap();
+        // an impossible path can still be partial, as long as it stops short of the end
+        assert_ne!(result.status, PathStatus::Complete, "Pathfinder completed an impossible path");
+        assert_ne!(result.end(), Some(end), "Pathfinder calculated an impossible path");
     }
 }
\ No newline at end of file
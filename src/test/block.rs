@@ -0,0 +1,110 @@
+use crate::config::Configuration;
+use crate::pathing::block::{BlockClass, BlockInfo, BlockRegistry, Climb, Fluid};
+use crate::pathing::chunk::{ChunkSection, AIR};
+use crate::pathing::data::HazardMultiplier;
+use crate::pathing::math::Vector3i;
+use crate::pathing::world::{Space, VoxelSpace};
+use crate::vec3i;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const TABLE: &str = r#"[
+    { "id": 1, "name": "minecraft:stone", "class": "solid" },
+    { "id": 2, "name": "minecraft:water", "class": { "liquid": "water" }, "properties": { "level": "0" } },
+    { "id": 3, "name": "minecraft:lava", "class": { "liquid": "lava" }, "properties": { "level": "0" } },
+    { "id": 4, "name": "minecraft:ladder", "class": { "climbable": "ladder" }, "properties": { "facing": "north" } },
+    { "id": 5, "name": "minecraft:fire", "class": "damaging" },
+    { "id": 6, "name": "minecraft:oak_slab", "class": "partial", "properties": { "type": "bottom" } },
+    { "id": 7, "name": "somemod:strange_rock", "class": "solid" }
+]"#;
+
+#[test]
+fn registry_defaults() {
+    let registry = BlockRegistry::new();
+    assert_eq!(registry.class_of(AIR), Some(BlockClass::Air), "Air was not registered by default");
+    assert_eq!(registry.class_of(1), None, "Unregistered block had a class");
+    assert_eq!(registry.len(), 1);
+}
+
+#[test]
+fn registry_from_json() {
+    let parsed = BlockRegistry::from_json(TABLE);
+    assert!(parsed.is_ok(), "Failed to parse the block table: {}", parsed.unwrap_err());
+    let registry = parsed.unwrap();
+
+    assert_eq!(registry.len(), 8, "Not every block was registered");
+    assert_eq!(registry.class_of(AIR), Some(BlockClass::Air), "Defaults were not kept");
+    assert_eq!(registry.class_of(2), Some(BlockClass::Liquid(Fluid::Water)));
+    assert_eq!(registry.class_of(3), Some(BlockClass::Liquid(Fluid::Lava)));
+    assert_eq!(registry.class_of(4), Some(BlockClass::Climbable(Climb::Ladder)));
+    assert_eq!(registry.class_of(7), Some(BlockClass::Solid), "Modded block was not registered");
+
+    let slab = registry.get(6);
+    assert!(slab.is_some(), "Couldn't get info for a registered block");
+    assert_eq!(slab.unwrap().name, "minecraft:oak_slab");
+    assert_eq!(slab.unwrap().property("type"), Some("bottom"), "State properties were not read");
+}
+
+#[test]
+fn registry_invalid_json() {
+    let unknown_class = r#"[{ "id": 1, "name": "minecraft:stone", "class": "bouncy" }]"#;
+    assert!(BlockRegistry::from_json(unknown_class).is_err(), "Parsed a nonexistent class");
+    let missing_id = r#"[{ "name": "minecraft:stone", "class": "solid" }]"#;
+    assert!(BlockRegistry::from_json(missing_id).is_err(), "Parsed a block without an ID");
+}
+
+#[test]
+fn registry_read_nonexistent() {
+    let path = PathBuf::from("nonsense_blocks.json");
+    assert!(BlockRegistry::read_registry(path.as_path()).is_err(), "Returned Ok() even when the file did not exist!");
+}
+
+#[test]
+fn register_replaces() {
+    let mut registry = BlockRegistry::new();
+    assert!(registry.register(10, BlockInfo::new("minecraft:glass", BlockClass::Solid)).is_none());
+    let previous = registry.register(10, BlockInfo::new("minecraft:glass_pane", BlockClass::Partial));
+    assert_eq!(previous.map(|p| p.class), Some(BlockClass::Solid), "Previous registration was not returned");
+    assert_eq!(registry.class_of(10), Some(BlockClass::Partial));
+}
+
+#[test]
+fn hazard_multipliers() {
+    let hazard = HazardMultiplier::new();
+    assert_eq!(hazard.multiplier_for(Some(BlockClass::Air)), Some(1.0));
+    assert_eq!(hazard.multiplier_for(Some(BlockClass::Solid)), None, "Solid blocks were passable");
+    assert_eq!(hazard.multiplier_for(None), None, "Unregistered blocks were passable");
+    let lenient = HazardMultiplier { unknown: 10, ..hazard };
+    assert_eq!(lenient.multiplier_for(None), Some(1.0));
+    let no_water = HazardMultiplier { non_solid: HazardMultiplier::IMPASSABLE, ..hazard };
+    assert_eq!(no_water.multiplier_for(Some(BlockClass::Liquid(Fluid::Water))), None, "Impassable multiplier was applied");
+    assert_eq!(hazard.multiplier_for(Some(BlockClass::Climbable(Climb::Ladder))), Some(2.1));
+    assert_eq!(hazard.multiplier_for(Some(BlockClass::Liquid(Fluid::Lava))), Some(5.0));
+}
+
+#[test]
+fn material_cost_classes() {
+    let mut config = Configuration::new();
+    config.hazard.unknown = 30;
+    let registry = Arc::new(BlockRegistry::from_json(TABLE).unwrap());
+    let space = VoxelSpace::new(config, registry);
+    space.load_section(vec3i!(0, 0, 0), ChunkSection::new());
+    for id in 1..=8u16 {
+        let _ = space.set_block(vec3i!(id as i32, 0, 0), id);
+    }
+
+    assert_eq!(space.material_cost(vec3i!(0, 0, 0)), 1.0, "Air was not passable");
+    assert_eq!(space.material_cost(vec3i!(1, 0, 0)), config.cost_inf, "Stone was passable");
+    assert_eq!(space.material_cost(vec3i!(2, 0, 0)), 2.1, "Water did not use the non-solid multiplier");
+    assert_eq!(space.material_cost(vec3i!(3, 0, 0)), 5.0, "Lava did not use the dangerous multiplier");
+    assert_eq!(space.material_cost(vec3i!(4, 0, 0)), 2.1, "Ladder did not use the non-solid multiplier");
+    assert_eq!(space.material_cost(vec3i!(5, 0, 0)), 5.0, "Fire did not use the dangerous multiplier");
+    assert_eq!(space.material_cost(vec3i!(6, 0, 0)), 1.0, "Slab did not use the solid multiplier");
+    assert_eq!(space.material_cost(vec3i!(7, 0, 0)), config.cost_inf, "Modded solid was passable");
+    assert_eq!(space.material_cost(vec3i!(8, 0, 0)), 3.0, "Unregistered block did not use the unknown multiplier");
+
+    let strict = VoxelSpace::new(Configuration::new(), Arc::new(BlockRegistry::from_json(TABLE).unwrap()));
+    strict.load_section(vec3i!(0, 0, 0), ChunkSection::new());
+    let _ = strict.set_block(vec3i!(8, 0, 0), 8);
+    assert_eq!(strict.material_cost(vec3i!(8, 0, 0)), config.cost_inf, "Unregistered block was passable by default");
+}
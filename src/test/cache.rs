@@ -0,0 +1,140 @@
+use crate::binding::cache;
+use crate::binding::jni::JNICompatible;
+use crate::binding::load::java_classes;
+use crate::pathing::action::{MoveAction, MoveKind, SpatialAction};
+use crate::pathing::data::{PathNode, PathResult};
+use crate::pathing::math::Vector3i;
+use crate::test::jni::with_env;
+use crate::vec3i;
+use jni::objects::{JObject, JValueGen};
+use jni::JNIEnv;
+use std::time::{Duration, Instant};
+
+#[test]
+fn preload_finds_every_class() {
+    with_env(|env| {
+        cache::preload(env, &java_classes());
+        assert!(!env.exception_check().unwrap(), "Preloading left an exception pending");
+        for class in java_classes() {
+            let found = cache::class(env, class);
+            assert!(found.is_ok(), "Failed to find {}: {}", class, found.unwrap_err());
+        }
+
+        let first = cache::class(env, Vector3i::CLASS).unwrap();
+        let second = cache::class(env, Vector3i::CLASS).unwrap();
+        assert!(env.is_same_object(&first, &second).unwrap(), "Class was looked up twice");
+    });
+}
+
+#[test]
+fn unchecked_calls_are_checked() {
+    with_env(|env| {
+        let pos = vec3i!(1, 2, 3).to_jni(env).unwrap();
+        assert_eq!(cache::call_method(env, &pos, Vector3i::CLASS, "y", "()I", &[]).unwrap().i().unwrap(), 2);
+
+        assert!(cache::call_method(env, &JObject::null(), Vector3i::CLASS, "y", "()I", &[]).is_err(),
+                "Called a method on null");
+        assert!(cache::call_method(env, &pos, MoveKind::CLASS, "ordinal", "()I", &[]).is_err(),
+                "Called a method on an instance of another class");
+        assert!(cache::call_method(env, &pos, Vector3i::CLASS, "y", "()I", &[JValueGen::Int(1)]).is_err(),
+                "Called a method with too many arguments");
+        assert!(cache::new_object(env, Vector3i::CLASS, "(III)V", &[
+            JValueGen::Int(1), JValueGen::Long(2), JValueGen::Int(3)
+        ]).is_err(), "Called a constructor with an argument of the wrong type");
+
+        let missing = cache::call_method(env, &pos, Vector3i::CLASS, "w", "()I", &[]);
+        assert!(missing.is_err(), "Called a method that doesn't exist");
+        assert!(env.exception_check().unwrap());
+        env.exception_clear().unwrap();
+    });
+}
+
+#[test]
+fn conversions_are_cached() {
+    with_env(|env| {
+        let result = PathResult::complete(vec![
+            PathNode::new(SpatialAction::new_root(vec3i!(0, 64, 0))),
+            PathNode::new(SpatialAction::new(vec3i!(1, 64, 0), MoveAction::new(1, vec3i!(1, 0, 0))))
+        ]);
+        let object = result.to_jni(env).unwrap();
+        assert!(cache::is_cached(Vector3i::CLASS, "<init>", "(III)V"), "Constructor was not cached");
+        assert!(cache::is_cached(MoveKind::CLASS, "TRAVERSE", ""), "Enum constant was not cached");
+
+        PathResult::<Vector3i>::from_jni(env, object).unwrap();
+        assert!(cache::is_cached(Vector3i::CLASS, "y", "()I"), "Accessor was not cached");
+        assert!(!cache::is_cached(Vector3i::CLASS, "w", "()I"));
+
+        let first = cache::enum_constant(env, MoveKind::CLASS, "TRAVERSE").unwrap();
+        let second = cache::enum_constant(env, MoveKind::CLASS, "TRAVERSE").unwrap();
+        assert!(env.is_same_object(&first, &second).unwrap(), "Cached enum constant was not the same object");
+    });
+}
+
+/// Compares against converting by name, which is only worth running by hand: timings are too noisy
+/// to fail a build over.
+#[test]
+#[ignore = "timing comparison, run with --ignored"]
+fn cached_conversion_speedup() {
+    with_env(|env| {
+        let path: Vec<PathNode<Vector3i>> = (0..1000)
+            .map(|idx| PathNode::new(SpatialAction::new(vec3i!(idx, 64, 0), MoveAction::new(1, vec3i!(1, 0, 0)))))
+            .collect();
+        let result = PathResult::complete(path);
+
+        // warm both up, so only the steady state is compared
+        result.to_jni(env).unwrap();
+        uncached_path(env, &result.path);
+        let cached = fastest(|| env.with_local_frame(16, |env| result.to_jni(env).map(|_| ())).unwrap());
+        let uncached = fastest(|| uncached_path(env, &result.path));
+
+        assert!(cached < uncached, "Cached conversion ({:?}) was no faster than by name ({:?})", cached, uncached);
+    });
+}
+
+/// Returns the quickest of a few runs, which is the least noisy.
+fn fastest(mut run: impl FnMut()) -> Duration {
+    (0..5)
+        .map(|_| {
+            let start_time = Instant::now();
+            run();
+            start_time.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+/// Converts a path the way it was before anything was cached: every class and method is looked up by
+/// name, for every single object.
+fn uncached_path(env: &mut JNIEnv, path: &[PathNode<Vector3i>]) {
+    env.with_local_frame(16, |env| -> jni::errors::Result<()> {
+        let list = env.new_object("java/util/ArrayList", "()V", &[])?;
+        for node in path {
+            env.with_local_frame(16, |env| -> jni::errors::Result<()> {
+                let action = node.action.move_action.unwrap();
+                let pos = uncached_vec(env, node.action.pos)?;
+                let offset = uncached_vec(env, action.offset)?;
+                let kind = env.get_static_field(MoveKind::CLASS, "TRAVERSE", "Lcom/genericbadname/s4mc/pathing/MoveKind;")?.l()?;
+                let action = env.new_object(SpatialAction::<Vector3i>::CLASS,
+                    "(Lcom/genericbadname/s4mc/math/Vector3i;Lcom/genericbadname/s4mc/pathing/MoveKind;JLcom/genericbadname/s4mc/math/Vector3i;)V", &[
+                    JValueGen::Object(&pos),
+                    JValueGen::Object(&kind),
+                    JValueGen::Long(action.cost as i64),
+                    JValueGen::Object(&offset)
+                ])?;
+                let interactions = env.new_object("java/util/ArrayList", "()V", &[])?;
+                let node = env.new_object(PathNode::<Vector3i>::CLASS,
+                    "(Lcom/genericbadname/s4mc/pathing/SpatialAction;Ljava/util/List;)V", &[
+                    JValueGen::Object(&action),
+                    JValueGen::Object(&interactions)
+                ])?;
+                env.call_method(&list, "add", "(Ljava/lang/Object;)Z", &[JValueGen::Object(&node)])?;
+                Ok(())
+            })?;
+        }
+        Ok(())
+    }).unwrap();
+}
+
+fn uncached_vec<'local>(env: &mut JNIEnv<'local>, pos: Vector3i) -> jni::errors::Result<JObject<'local>> {
+    env.new_object(Vector3i::CLASS, "(III)V", &[JValueGen::Int(pos.x), JValueGen::Int(pos.y), JValueGen::Int(pos.z)])
+}
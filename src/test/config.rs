@@ -1,4 +1,4 @@
-use crate::config::Configuration;
+use crate::config::{ClimbCost, Configuration, CornerCutting, SwimTicks};
 use crate::pathing::data::HazardMultiplier;
 use std::path::PathBuf;
 use std::time::Duration;
@@ -34,6 +34,26 @@ fn equality() {
         },
         cost_inf: 11.0,
         timeout: Duration::from_millis(13),
+        max_health: 17.0,
+        damage_cost: 19.0,
+        corner_cutting: CornerCutting::AllowOneSide,
+        allow_parkour: false,
+        parkour_cost: 23.0,
+        allow_breaking: false,
+        break_cost: 29.0,
+        allow_placing: false,
+        place_cost: 31.0,
+        climb_cost: ClimbCost {
+            ladder: 37.0,
+            vine: 41.0,
+            scaffolding: 43.0
+        },
+        swim_ticks: SwimTicks {
+            surface: 47.0,
+            diving: 53.0,
+            current: 59.0
+        },
+        open_cost: 61.0,
     };
 
     let config2 = Configuration {
@@ -45,7 +65,54 @@ fn equality() {
         },
         cost_inf: 11.0,
         timeout: Duration::from_millis(13),
+        max_health: 17.0,
+        damage_cost: 19.0,
+        corner_cutting: CornerCutting::AllowOneSide,
+        allow_parkour: false,
+        parkour_cost: 23.0,
+        allow_breaking: false,
+        break_cost: 29.0,
+        allow_placing: false,
+        place_cost: 31.0,
+        climb_cost: ClimbCost {
+            ladder: 37.0,
+            vine: 41.0,
+            scaffolding: 43.0
+        },
+        swim_ticks: SwimTicks {
+            surface: 47.0,
+            diving: 53.0,
+            current: 59.0
+        },
+        open_cost: 61.0,
     };
 
     assert_eq!(config1, config2, "Configurations were not equal!");
+}
+
+#[test]
+fn read_missing_fields() {
+    let read = serde_json::from_str::<Configuration>(r#"{ "cost_inf": 11.0 }"#);
+    assert!(read.is_ok(), "Reading a partial config failed: {}", read.unwrap_err());
+    let config = read.unwrap();
+    assert_eq!(config.cost_inf, 11.0, "Present field was not read");
+    assert_eq!(config.max_health, Configuration::new().max_health, "Missing field was not defaulted");
+}
+
+#[test]
+fn from_json() {
+    let read = Configuration::from_json(r#"{ "allow_breaking": true }"#);
+    assert!(read.is_ok(), "Parsing a partial config failed: {}", read.unwrap_err());
+    assert_eq!(read.unwrap(), Configuration { allow_breaking: true, ..Configuration::new() });
+    assert_eq!(Configuration::from_json("{}").unwrap(), Configuration::new(), "Empty config was not the default");
+    assert!(Configuration::from_json(r#"{ "allow_breaking": 1 }"#).is_err(), "Parsed a mistyped field");
+}
+
+#[test]
+fn corner_cutting() {
+    assert!(CornerCutting::Forbid.allows(false, false));
+    assert!(!CornerCutting::Forbid.allows(true, false), "Cut a corner with a blocked side");
+    assert!(CornerCutting::AllowOneSide.allows(false, true), "Did not cut a corner with a clear side");
+    assert!(!CornerCutting::AllowOneSide.allows(true, true), "Cut a corner with both sides blocked");
+    assert!(CornerCutting::Allow.allows(true, true), "Did not cut a corner");
 }
\ No newline at end of file
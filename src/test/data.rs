@@ -1,11 +1,12 @@
 use std::cmp::Reverse;
 use std::collections::BinaryHeap;
 use std::time::Instant;
-use crate::pathing::data::{BinaryHeapOpenSet, Node};
-use crate::pathing::math::Vector2i;
-use eyre::{Report, Result};
-use log::info;
-use rand::rngs::{SmallRng, StdRng};
+use crate::pathing::algorithm::GraphPosition;
+use crate::pathing::data::{BinaryHeapOpenSet, Node, NodeIndexMap, PathState};
+use crate::pathing::math::{Vector2i, Vector3i};
+use crate::{vec2i, vec3i};
+use eyre::Result;
+use rand::rngs::SmallRng;
 use rand::{Rng, SeedableRng};
 use crate::pathing::action::SpatialAction;
 
@@ -20,7 +21,7 @@ fn heap_ordered() {
     results.retain(|r| r.is_err());
 
     assert!(results.is_empty(), "A heap operation failed: {}", results[0].as_ref().unwrap_err());
-    let actual = hs.heap.cost_order();
+    let actual = hs.heap.cost_order(&hs.nodes);
     let intended = vec![0.0, 1.0, 2.0];
     assert_eq!(actual, intended, "Heap did not match the binary heap structure")
 }
@@ -38,14 +39,16 @@ fn heap_unordered() {
     ];
     results.retain(|r| r.is_err());
     assert!(results.is_empty(), "A heap operation failed: {}", results[0].as_ref().unwrap_err());
-    let actual = hs.heap.cost_order();
+    let actual = hs.heap.cost_order(&hs.nodes);
     let intended = vec![0.0, 1.0, 2.0, 5.0, 3.0, 4.0];
     assert_eq!(actual, intended, "Heap did not match the binary heap structure");
 
-    let lowest = hs.heap.pop();
+    let lowest = hs.heap.pop(&mut hs.nodes);
     assert!(lowest.is_some(), "Heap did not successfully pop off a value");
-    assert_eq!(lowest.unwrap().f_cost(), 0.0, "Heap did not pop off the lowest value");
-    let actual2 = hs.heap.cost_order();
+    let lowest_node = hs.nodes[lowest.unwrap() as usize];
+    assert_eq!(lowest_node.f_cost(), 0.0, "Heap did not pop off the lowest value");
+    assert!(!lowest_node.is_open(), "Popped node was still open");
+    let actual2 = hs.heap.cost_order(&hs.nodes);
     let intended2 = vec![1.0, 3.0, 2.0, 5.0, 4.0];
     assert_eq!(actual2, intended2, "Heap did not match the binary heap structure")
 }
@@ -68,50 +71,127 @@ fn against_std_100000() {
 fn against_std_range(entries: usize) {
     let mut bh_std = BinaryHeap::new();
     let mut bh_custom = BinaryHeapOpenSet::new();
+    let mut nodes: Vec<Node<Vector2i>> = Vec::with_capacity(entries);
     let mut rng = SmallRng::seed_from_u64(1);
     let start_time = Instant::now();
 
-    for _ in 0..entries {
+    for i in 0..entries {
         let node = Node {
             g_cost: rng.random_range(0..1000) as f64,
             h_cost: rng.random_range(0..1000) as f64,
             action: SpatialAction::new_root(Vector2i::zero()),
             parent: None,
-            heap_idx: None
+            heap_idx: None,
+            state: PathState::default(),
+            interactions: None
         };
         bh_std.push(Reverse(node));
-        let res = bh_custom.insert_direct(node);
+        nodes.push(node);
+        let res = bh_custom.insert(&mut nodes, i as u32);
         assert!(res.is_ok(), "Failed to insert into BinaryHeapOpenSet: {}", res.unwrap_err())
     }
 
     let val_std = bh_std.into_vec();
-    let val_custom = bh_custom.clone_data();
+    let val_custom: Vec<Node<Vector2i>> = bh_custom.indices().iter()
+        .map(|idx| nodes[*idx as usize])
+        .collect();
 
     assert_eq!(val_std.len(), val_custom.len(), "Heaps did not have a matching length");
     for i in 0..entries {
         assert_eq!(val_std[i].0, val_custom[i], "Values did not match: {:?} (std::BinaryHeap), {:?} (BinaryHeapOpenSet)",
                    val_std[i].0, val_custom[i]
         );
+        assert_eq!(val_custom[i].heap_idx, Some(i), "Node heap index did not match its position in the heap");
     }
 
     println!("std comparison took {} ms", start_time.elapsed().as_millis())
 }
 
+#[test]
+fn heap_decrease_key() {
+    let mut hs = HeapScenario::new();
+    let mut results = vec![
+        hs.dummy_node(1.0, 0.0),
+        hs.dummy_node(2.0, 0.0),
+        hs.dummy_node(3.0, 0.0),
+        hs.dummy_node(4.0, 0.0)
+    ];
+    results.retain(|r| r.is_err());
+    assert!(results.is_empty(), "A heap operation failed: {}", results[0].as_ref().unwrap_err());
+
+    // make the most expensive node the cheapest
+    hs.nodes[3].g_cost = 0.5;
+    let res = hs.heap.sift_up(&mut hs.nodes, 3);
+    assert!(res.is_ok(), "Failed to decrease a key: {}", res.unwrap_err());
+    assert_eq!(hs.nodes[3].heap_idx, Some(0), "Decreased node did not become the root");
+    assert_eq!(hs.heap.pop(&mut hs.nodes), Some(3), "Heap did not pop off the decreased node");
+
+    assert!(hs.heap.sift_up(&mut hs.nodes, 3).is_err(), "Sifted a Closed node");
+}
+
+#[test]
+fn node_index_map() {
+    let mut map = NodeIndexMap::with_capacity(4);
+    assert!(map.is_empty());
+    assert_eq!(map.get(7), None);
+
+    // enough entries to force several resizes
+    for i in 0..10_000i64 {
+        assert_eq!(map.insert(i * 31 - 5_000, i as u32), None, "New key reported a previous value");
+    }
+    assert_eq!(map.len(), 10_000);
+    for i in 0..10_000i64 {
+        assert_eq!(map.get(i * 31 - 5_000), Some(i as u32), "Value was lost");
+    }
+    assert_eq!(map.insert(-5_000, 42), Some(0), "Replacing a value did not return the previous one");
+    assert_eq!(map.get(-5_000), Some(42));
+    assert_eq!(map.len(), 10_000, "Replacing a value changed the length");
+
+    map.clear();
+    assert!(map.is_empty());
+    assert_eq!(map.get(31 - 5_000), None, "Value survived clearing");
+}
+
+#[test]
+fn position_packing() {
+    let positions = [
+        vec3i!(0, 0, 0), vec3i!(1, 0, 0), vec3i!(0, 1, 0), vec3i!(0, 0, 1),
+        vec3i!(-1, 0, 0), vec3i!(0, -1, 0), vec3i!(0, 0, -1),
+        vec3i!(30_000_000, 320, -30_000_000), vec3i!(-30_000_000, -64, 30_000_000),
+        // the edges of the packable range
+        vec3i!(0, 2047, 0), vec3i!(0, -2048, 0), vec3i!((1 << 25) - 1, 0, 0), vec3i!(-(1 << 25), 0, 0)
+    ];
+    for (i, a) in positions.iter().enumerate() {
+        for b in positions.iter().skip(i + 1) {
+            assert_ne!(a.pack(), b.pack(), "{} and {} packed to the same key", a, b);
+        }
+    }
+
+    assert_ne!(vec2i!(1, 0).pack(), vec2i!(0, 1).pack());
+    assert_ne!(vec2i!(-1, 0).pack(), vec2i!(0, -1).pack());
+    assert_ne!(vec2i!(0, -1).pack(), vec2i!(-1, -1).pack());
+}
+
 struct HeapScenario {
-    heap: BinaryHeapOpenSet<Vector2i>
+    heap: BinaryHeapOpenSet,
+    nodes: Vec<Node<Vector2i>>
 }
 
 impl HeapScenario {
     fn new() -> Self {
-        Self { heap: BinaryHeapOpenSet::new() }
+        Self { heap: BinaryHeapOpenSet::new(), nodes: Vec::new() }
     }
     fn dummy_node(&mut self, g_cost: f64, h_cost: f64) -> Result<()> {
-        self.heap.insert_direct(Node {
+        self.nodes.push(Node {
             g_cost,
             h_cost,
             action: SpatialAction::new_root(Vector2i::zero()),
             parent: None,
             heap_idx: None,
-        })
+            state: PathState::default(),
+            interactions: None
+        });
+        let idx = (self.nodes.len() - 1) as u32;
+        self.heap.insert(&mut self.nodes, idx)
     }
-}
\ No newline at end of file
+}
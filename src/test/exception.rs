@@ -0,0 +1,63 @@
+use crate::binding::exception::{exception_message, guard, panic_message, NATIVE_EXCEPTION};
+use crate::test::jni::with_env;
+use eyre::{eyre, WrapErr};
+use jni::JNIEnv;
+use std::panic;
+
+#[test]
+fn message_has_whole_chain() {
+    let error = Err::<(), _>(eyre!("Palette index 9 out of bounds"))
+        .wrap_err("Failed to load section")
+        .unwrap_err();
+    assert_eq!(exception_message(&error), "Failed to load section: Palette index 9 out of bounds",
+               "Exception message did not contain every cause");
+}
+
+#[test]
+fn panic_messages() {
+    let literal = panic::catch_unwind(|| panic!("not implemented")).unwrap_err();
+    assert_eq!(panic_message(literal.as_ref()), "not implemented");
+
+    let formatted = panic::catch_unwind(|| panic!("index {} out of range", 3)).unwrap_err();
+    assert_eq!(panic_message(formatted.as_ref()), "index 3 out of range", "Formatted panic message was lost");
+
+    let other = panic::catch_unwind(|| panic::panic_any(5)).unwrap_err();
+    assert_eq!(panic_message(other.as_ref()), "no message");
+}
+
+/// Takes the pending exception, asserting that it's a `NativeException`, and returns its message.
+fn take_native_exception(env: &mut JNIEnv) -> String {
+    let exception = env.exception_occurred().unwrap();
+    assert!(!exception.is_null(), "No exception was thrown");
+    env.exception_clear().unwrap();
+    assert!(env.is_instance_of(&exception, NATIVE_EXCEPTION).unwrap(), "Thrown exception was not a NativeException");
+
+    let message = env.call_method(&exception, "getMessage", "()Ljava/lang/String;", &[]).unwrap().l().unwrap();
+    env.get_string(&message.into()).unwrap().into()
+}
+
+#[test]
+fn guard_returns_value() {
+    with_env(|env| {
+        assert_eq!(guard(env, 0, |_env| Ok(7)), 7);
+        assert!(!env.exception_check().unwrap(), "Successful body threw");
+    });
+}
+
+#[test]
+fn guard_throws_errors() {
+    with_env(|env| {
+        let out = guard(env, -1, |_env| Err(eyre!("Calculator 3 is unknown")));
+        assert_eq!(out, -1, "Fallback was not returned");
+        assert_eq!(take_native_exception(env), "Calculator 3 is unknown");
+    });
+}
+
+#[test]
+fn guard_catches_panics() {
+    with_env(|env| {
+        let out = guard(env, -1, |_env| -> eyre::Result<i32> { panic!("index 3 out of range") });
+        assert_eq!(out, -1, "Fallback was not returned");
+        assert_eq!(take_native_exception(env), "Native code panicked: index 3 out of range");
+    });
+}
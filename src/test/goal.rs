@@ -0,0 +1,153 @@
+use crate::config::Configuration;
+use crate::pathing::action::moveset_2d_cardinal;
+use crate::pathing::algorithm::PathCalculator;
+use crate::pathing::data::PathStatus;
+use crate::pathing::goal::{Goal, GoalComposite, GoalExact, GoalInverted, GoalNear, GoalXZ, GoalYLevel};
+use crate::pathing::math::{Vector2i, Vector3i};
+use crate::pathing::world::FlatSpace;
+use crate::{vec2i, vec3i};
+use std::time::Duration;
+
+fn calculator(environment: Vec<&'static str>) -> PathCalculator<Vector2i, FlatSpace> {
+    let config = Configuration::new();
+    let space = Box::new(FlatSpace::new(environment, config));
+    PathCalculator::new(moveset_2d_cardinal(), config, space)
+}
+
+#[test]
+fn goal_exact() {
+    let goal = GoalExact(vec2i!(3, 4));
+    assert!(goal.is_goal(vec2i!(3, 4)));
+    assert!(!goal.is_goal(vec2i!(4, 3)));
+    assert_eq!(goal.heuristic(vec2i!(0, 0)), 5.0);
+    assert_eq!(goal.heuristic(vec2i!(3, 4)), 0.0);
+}
+
+#[test]
+fn goal_near() {
+    let goal = GoalNear::new(vec2i!(0, 0), 2.0);
+    assert!(goal.is_goal(vec2i!(0, 2)));
+    assert!(goal.is_goal(vec2i!(1, 1)));
+    assert!(!goal.is_goal(vec2i!(2, 2)));
+    assert_eq!(goal.heuristic(vec2i!(0, 5)), 3.0);
+    assert_eq!(goal.heuristic(vec2i!(0, 1)), 0.0, "Heuristic inside the radius was not zero");
+}
+
+#[test]
+fn goal_xz_and_y_level() {
+    let xz = GoalXZ::new(3, -4);
+    assert!(xz.is_goal(vec3i!(3, 100, -4)));
+    assert!(!xz.is_goal(vec3i!(3, 100, 4)));
+    assert_eq!(xz.heuristic(vec3i!(0, 64, 0)), 5.0, "Heuristic accounted for height");
+
+    let y = GoalYLevel(12);
+    assert!(y.is_goal(vec3i!(-50, 12, 300)));
+    assert!(!y.is_goal(vec3i!(0, 11, 0)));
+    assert_eq!(y.heuristic(vec3i!(0, 64, 0)), 52.0);
+}
+
+#[test]
+fn goal_composite() {
+    let goal: GoalComposite<Vector2i> = GoalComposite::new(vec![
+        Box::new(GoalExact(vec2i!(10, 0))),
+        Box::new(GoalExact(vec2i!(0, 3)))
+    ]);
+    assert!(goal.is_goal(vec2i!(10, 0)));
+    assert!(goal.is_goal(vec2i!(0, 3)));
+    assert!(!goal.is_goal(vec2i!(0, 0)));
+    assert_eq!(goal.heuristic(vec2i!(0, 0)), 3.0, "Heuristic did not use the closest goal");
+
+    let empty: GoalComposite<Vector2i> = GoalComposite::new(vec![]);
+    assert!(!empty.is_goal(vec2i!(0, 0)), "Empty composite goal was satisfied");
+}
+
+#[test]
+fn goal_inverted() {
+    let goal = GoalInverted::new(Box::new(GoalExact(vec2i!(0, 0))), 3.0);
+    assert!(!goal.is_goal(vec2i!(0, 0)));
+    assert!(!goal.is_goal(vec2i!(2, 0)), "Inverted goal was satisfied too close");
+    assert!(goal.is_goal(vec2i!(3, 0)));
+    assert!(goal.is_goal(vec2i!(5, 5)));
+    assert!(goal.heuristic(vec2i!(4, 0)) < goal.heuristic(vec2i!(1, 0)), "Moving away was not favored");
+
+    let endless = GoalInverted::new(Box::new(GoalExact(vec2i!(0, 0))), f64::INFINITY);
+    assert!(!endless.is_goal(vec2i!(1000, 1000)), "Inverted goal without a distance was satisfied");
+}
+
+#[test]
+fn pathfinder_goal_inverted() {
+    let mut calc = calculator(vec![
+        "______",
+        "_XXXX_",
+        "O_____"
+    ]);
+
+    let away = GoalInverted::new(Box::new(GoalExact(vec2i!(0, 2))), 3.0);
+    let out = calc.calculate_to_goal(vec2i!(0, 2), &away);
+    assert!(out.is_ok(), "Pathfinding failed with error: {}", out.unwrap_err());
+    let result = out.unwrap();
+    assert!(result.is_complete(), "Pathfinder did not get far enough away");
+    assert_eq!(result.end(), Some(vec2i!(3, 2)), "Pathfinder did not stop at the distance");
+
+    // without a distance, the search ends by running out of nodes rather than time
+    let mut config = Configuration::new();
+    config.timeout = Duration::from_secs(600);
+    let space = Box::new(FlatSpace::new(vec![
+        "______",
+        "_XXXX_",
+        "O_____"
+    ], config));
+    let mut calc = PathCalculator::new(moveset_2d_cardinal(), config, space);
+    let away = GoalInverted::new(Box::new(GoalExact(vec2i!(0, 2))), f64::INFINITY);
+    let out = calc.calculate_to_goal(vec2i!(0, 2), &away);
+    assert!(out.is_ok(), "Pathfinding failed with error: {}", out.unwrap_err());
+    let result = out.unwrap();
+    assert_eq!(result.status, PathStatus::Partial, "Exhausted search was not partial");
+    assert_eq!(result.end(), Some(vec2i!(5, 0)), "Partial path did not end at the farthest cell");
+}
+
+#[test]
+fn pathfinder_goal_near() {
+    let mut calc = calculator(vec![
+        "O____",
+        "XXXX_",
+        "_____",
+        "_XXXX",
+        "____G"
+    ]);
+
+    let out = calc.calculate_to_goal(vec2i!(0, 0), &GoalNear::new(vec2i!(4, 4), 1.0));
+    assert!(out.is_ok(), "Pathfinding failed with error: {}", out.unwrap_err());
+    assert_eq!(out.unwrap().end(), Some(vec2i!(3, 4)), "Pathfinder did not stop at the radius");
+}
+
+#[test]
+fn pathfinder_goal_composite() {
+    let mut calc = calculator(vec![
+        "G___O_____G"
+    ]);
+
+    let goal: GoalComposite<Vector2i> = GoalComposite::new(vec![
+        Box::new(GoalExact(vec2i!(0, 0))),
+        Box::new(GoalExact(vec2i!(10, 0)))
+    ]);
+    let out = calc.calculate_to_goal(vec2i!(4, 0), &goal);
+    assert!(out.is_ok(), "Pathfinding failed with error: {}", out.unwrap_err());
+    let result = out.unwrap();
+    assert_eq!(result.end(), Some(vec2i!(0, 0)), "Pathfinder did not reach the closest goal");
+    assert_eq!(result.path.len(), 5);
+}
+
+#[test]
+fn pathfinder_exact_matches_calculate() {
+    let mut calc = calculator(vec![
+        "O_X__",
+        "__X__",
+        "____G"
+    ]);
+
+    let direct = calc.calculate(vec2i!(0, 0), vec2i!(4, 2));
+    let through_goal = calc.calculate_to_goal(vec2i!(0, 0), &GoalExact(vec2i!(4, 2)));
+    assert!(direct.is_ok() && through_goal.is_ok(), "Pathfinding failed");
+    assert_eq!(direct.unwrap(), through_goal.unwrap(), "Exact goal did not match calculate()");
+}
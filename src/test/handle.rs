@@ -0,0 +1,48 @@
+use crate::binding::handle::HandleRegistry;
+use std::sync::Arc;
+
+#[test]
+fn insert_get_remove() {
+    let registry = HandleRegistry::new();
+    let first = registry.insert("first");
+    let second = registry.insert("second");
+    assert_ne!(first, 0, "Handed out a null handle");
+    assert_ne!(first, second, "Handed out the same handle twice");
+    assert_eq!(registry.len(), 2);
+
+    assert_eq!(registry.get(first).map(|v| *v).ok(), Some("first"), "Handle did not point to its object");
+    assert!(registry.remove(first).is_ok(), "Failed to free a live handle");
+    assert!(registry.get(first).is_err(), "Used a handle after freeing it");
+    assert_eq!(registry.get(second).map(|v| *v).ok(), Some("second"), "Freeing affected another handle");
+    assert!(registry.get(0).is_err(), "Null handle was valid");
+}
+
+#[test]
+fn double_free() {
+    let registry = HandleRegistry::new();
+    let handle = registry.insert(5);
+    assert!(registry.remove(handle).is_ok());
+    assert!(registry.remove(handle).is_err(), "Freed the same handle twice");
+    assert!(registry.is_empty());
+}
+
+#[test]
+fn handles_not_reused() {
+    let registry = HandleRegistry::new();
+    let freed = registry.insert(1);
+    registry.remove(freed).unwrap();
+    let next = registry.insert(2);
+    assert_ne!(freed, next, "Freed handle was reused");
+    assert!(registry.get(freed).is_err(), "Stale handle pointed to a new object");
+}
+
+#[test]
+fn free_while_in_use() {
+    let registry = HandleRegistry::new();
+    let handle = registry.insert(String::from("world"));
+    let in_use = registry.get(handle).unwrap();
+    registry.remove(handle).unwrap();
+
+    assert_eq!(*in_use, "world", "Object was dropped while still in use");
+    assert_eq!(Arc::strong_count(&in_use), 1, "Registry kept a reference after freeing");
+}
@@ -0,0 +1,7 @@
+package com.genericbadname.s4mc;
+
+public class NativeException extends RuntimeException {
+    public NativeException(String message) {
+        super(message);
+    }
+}
@@ -0,0 +1,16 @@
+package com.genericbadname.s4mc;
+
+import java.util.ArrayList;
+import java.util.Collections;
+import java.util.List;
+
+/** Stands in for the mod's bridge to SLF4J by keeping every record as "level target: message". */
+public final class NativeLog {
+    public static final List<String> RECORDS = Collections.synchronizedList(new ArrayList<>());
+
+    public static void log(int level, String target, String message) {
+        RECORDS.add(level + " " + target + ": " + message);
+    }
+
+    public static native void setMaxLevel(int level);
+}
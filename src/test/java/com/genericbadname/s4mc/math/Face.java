@@ -0,0 +1,5 @@
+package com.genericbadname.s4mc.math;
+
+public enum Face {
+    DOWN, UP, NORTH, SOUTH, WEST, EAST
+}
@@ -0,0 +1,3 @@
+package com.genericbadname.s4mc.math;
+
+public record Vector3i(int x, int y, int z) {}
@@ -0,0 +1,3 @@
+package com.genericbadname.s4mc.pathing;
+
+public record Agent(Hitbox hitbox, Tool tool, int throwawayBlocks, int airSupply, boolean fireResistant) {}
@@ -0,0 +1,3 @@
+package com.genericbadname.s4mc.pathing;
+
+public record Hitbox(double width, double height) {}
@@ -0,0 +1,7 @@
+package com.genericbadname.s4mc.pathing;
+
+import com.genericbadname.s4mc.math.Face;
+import com.genericbadname.s4mc.math.Vector3i;
+
+/** {@code face} is only set for {@code PLACE}. */
+public record Interaction(InteractionKind kind, Vector3i pos, Face face) {}
@@ -0,0 +1,5 @@
+package com.genericbadname.s4mc.pathing;
+
+public enum InteractionKind {
+    BREAK, PLACE, OPEN
+}
@@ -0,0 +1,5 @@
+package com.genericbadname.s4mc.pathing;
+
+public enum MoveKind {
+    TRAVERSE, DIAGONAL, ASCEND, DESCEND, FALL, PARKOUR, TUNNEL, BRIDGE, PILLAR, CLIMB, SWIM
+}
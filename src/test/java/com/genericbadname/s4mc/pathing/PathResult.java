@@ -0,0 +1,5 @@
+package com.genericbadname.s4mc.pathing;
+
+import java.util.List;
+
+public record PathResult(PathStatus status, List<PathNode> path) {}
@@ -0,0 +1,5 @@
+package com.genericbadname.s4mc.pathing;
+
+public enum PathStatus {
+    COMPLETE, PARTIAL, FAILED, CANCELLED
+}
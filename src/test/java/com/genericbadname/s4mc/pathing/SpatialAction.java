@@ -0,0 +1,6 @@
+package com.genericbadname.s4mc.pathing;
+
+import com.genericbadname.s4mc.math.Vector3i;
+
+/** {@code kind} and {@code offset} are null, and {@code cost} is 0, for the start of a path. */
+public record SpatialAction(Vector3i pos, MoveKind kind, long cost, Vector3i offset) {}
@@ -0,0 +1,8 @@
+package com.genericbadname.s4mc.test;
+
+import com.genericbadname.s4mc.math.Vector3i;
+import java.util.List;
+
+/** Exercises every kind of component a derived conversion supports. */
+public record Sample(byte b, short s, char c, int i, long l, float f, double d, boolean z,
+                     Vector3i pos, List<Vector3i> positions, double moveCost, int renamed) {}
@@ -0,0 +1,254 @@
+use crate::binding::jni::JNICompatible;
+use crate::pathing::action::{Interaction, MoveAction, MoveKind, SpatialAction};
+use crate::pathing::agent::{Agent, Hitbox, Tool};
+use crate::pathing::block::ToolKind;
+use crate::pathing::data::{PathNode, PathResult, PathStatus};
+use crate::pathing::math::{Face, Vector3i};
+use crate::vec3i;
+use jni::objects::{JObject, JValueGen};
+use jni::{InitArgsBuilder, JNIEnv, JNIVersion, JavaVM};
+use std::fmt::Debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::LazyLock;
+
+/// Stubs of the Java classes the bindings expect, standing in for the mod.
+const JAVA_SOURCES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/test/java");
+
+/// Where the stubs are compiled to.
+const JAVA_CLASSES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/target/test-classes");
+
+/// Only one JVM can ever be started per process, so every test shares this one.
+static JVM: LazyLock<JavaVM> = LazyLock::new(|| {
+    let classes = Path::new(JAVA_CLASSES);
+    let mut sources = Vec::new();
+    find_sources(Path::new(JAVA_SOURCES), &mut sources);
+
+    let status = Command::new("javac")
+        .arg("-d").arg(classes)
+        .args(&sources)
+        .status()
+        .expect("Failed to run javac");
+    assert!(status.success(), "Failed to compile the Java stubs");
+
+    let args = InitArgsBuilder::new()
+        .version(JNIVersion::V8)
+        .option(format!("-Djava.class.path={}", classes.display()))
+        .build()
+        .expect("Invalid JVM arguments");
+    JavaVM::new(args).expect("Failed to start the JVM")
+});
+
+fn find_sources(dir: &Path, out: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).expect("Failed to list the Java stubs") {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            find_sources(&path, out);
+        } else if path.extension().is_some_and(|ext| ext == "java") {
+            out.push(path);
+        }
+    }
+}
+
+/// Returns the shared JVM, started the first time.
+pub(super) fn jvm() -> &'static JavaVM {
+    &JVM
+}
+
+/// Runs `test` on this thread, attached to the shared JVM.
+pub(super) fn with_env(test: impl FnOnce(&mut JNIEnv)) {
+    let mut env = JVM.attach_current_thread().expect("Failed to attach to the JVM");
+    test(&mut env);
+}
+
+#[derive(Debug, Clone, PartialEq, JNICompatible)]
+#[jni(class = "com/genericbadname/s4mc/test/Sample")]
+struct Sample {
+    b: i8,
+    s: i16,
+    c: u16,
+    i: i32,
+    l: i64,
+    f: f32,
+    d: f64,
+    z: bool,
+    pos: Vector3i,
+    positions: Vec<Vector3i>,
+    move_cost: f64,
+    #[jni(name = "renamed")]
+    original: i32
+}
+
+fn round_trip<'local, T>(env: &mut JNIEnv<'local>, value: &T) -> T where T: JNICompatible<'local> + Debug {
+    let object = value.to_jni(env);
+    assert!(object.is_ok(), "Failed to convert {:?} to Java: {}", value, object.unwrap_err());
+    let back = T::from_jni(env, object.unwrap());
+    assert!(back.is_ok(), "Failed to convert {:?} back from Java: {}", value, back.unwrap_err());
+    back.unwrap()
+}
+
+#[test]
+fn derived_round_trip() {
+    with_env(|env| {
+        let sample = Sample {
+            b: -8, s: -16, c: 'c' as u16, i: -32, l: i64::MAX, f: 0.5, d: -2.25, z: true,
+            pos: vec3i!(1, 2, 3),
+            positions: vec![vec3i!(4, 5, 6), vec3i!(7, 8, 9)],
+            move_cost: 4.633,
+            original: 17
+        };
+        assert_eq!(round_trip(env, &sample), sample);
+
+        // components are named the Java way
+        let object = sample.to_jni(env).unwrap();
+        assert_eq!(env.call_method(&object, "moveCost", "()D", &[]).unwrap().d().unwrap(), 4.633);
+        assert_eq!(env.call_method(&object, "renamed", "()I", &[]).unwrap().i().unwrap(), 17);
+        assert!(env.call_method(&object, "z", "()Z", &[]).unwrap().z().unwrap());
+    });
+}
+
+#[test]
+fn enums_round_trip() {
+    with_env(|env| {
+        for kind in MoveKind::ALL {
+            assert_eq!(round_trip(env, &kind), kind);
+        }
+        for face in Face::ALL {
+            assert_eq!(round_trip(env, &face), face);
+        }
+        for kind in ToolKind::ALL {
+            assert_eq!(round_trip(env, &kind), kind);
+        }
+        for status in [PathStatus::Complete, PathStatus::Partial, PathStatus::Failed, PathStatus::Cancelled] {
+            assert_eq!(round_trip(env, &status), status);
+        }
+    });
+}
+
+#[test]
+fn spatial_action_round_trip() {
+    with_env(|env| {
+        let root = SpatialAction::new_root(vec3i!(1, 64, -3));
+        assert_eq!(round_trip(env, &root), root, "Root action did not survive the round trip");
+
+        let ascend = SpatialAction::new(vec3i!(2, 65, -3), MoveAction::of_kind(MoveKind::Ascend, 2, vec3i!(1, 1, 0)));
+        assert_eq!(round_trip(env, &ascend), ascend);
+
+        // the Java side gets a readable record, not just something that converts back
+        let object = ascend.to_jni(env).unwrap();
+        let cost = env.call_method(&object, "cost", "()J", &[]).unwrap().j().unwrap();
+        assert_eq!(cost, 2);
+        let pos = env.call_method(&object, "pos", "()Lcom/genericbadname/s4mc/math/Vector3i;", &[]).unwrap().l().unwrap();
+        assert_eq!(env.call_method(&pos, "y", "()I", &[]).unwrap().i().unwrap(), 65);
+    });
+}
+
+#[test]
+fn interactions_round_trip() {
+    with_env(|env| {
+        let interactions = vec![
+            Interaction::Break(vec3i!(0, 1, 0)),
+            Interaction::Place { pos: vec3i!(3, 0, 0), face: Face::West },
+            Interaction::Open(vec3i!(-4, 70, 12))
+        ];
+        assert_eq!(round_trip(env, &interactions), interactions);
+    });
+}
+
+#[test]
+fn path_result_round_trip() {
+    with_env(|env| {
+        let result = PathResult::partial(vec![
+            PathNode::new(SpatialAction::new_root(vec3i!(0, 0, 0))),
+            PathNode::with_interactions(
+                SpatialAction::new(vec3i!(1, 0, 0), MoveAction::of_kind(MoveKind::Tunnel, 1, vec3i!(1, 0, 0))),
+                vec![Interaction::Break(vec3i!(1, 1, 0)), Interaction::Break(vec3i!(1, 0, 0))]
+            ),
+            PathNode::with_interactions(
+                SpatialAction::new(vec3i!(2, 0, 0), MoveAction::new(1, vec3i!(1, 0, 0))),
+                vec![Interaction::Open(vec3i!(2, 0, 0))]
+            )
+        ]);
+        assert_eq!(round_trip(env, &result), result);
+        assert_eq!(round_trip(env, &PathResult::<Vector3i>::failed()), PathResult::failed());
+    });
+}
+
+#[test]
+fn agent_round_trip() {
+    with_env(|env| {
+        let agent = Agent {
+            hitbox: Hitbox::new(0.9, 1.4),
+            tool: Some(Tool::new(ToolKind::Pickaxe, 8.0)),
+            throwaway_blocks: 64,
+            air_supply: 120,
+            fire_resistant: true
+        };
+        assert_eq!(round_trip(env, &agent), agent);
+        assert_eq!(round_trip(env, &Agent::new()), Agent::new(), "Empty hand was not passed as null");
+        assert_eq!(Option::<Agent>::from_jni(env, JObject::null()).unwrap(), None);
+    });
+}
+
+#[test]
+fn long_list_round_trip() {
+    with_env(|env| {
+        // far more elements than a single local frame holds references
+        let positions: Vec<Vector3i> = (0..5000).map(|idx| vec3i!(idx, idx % 384, -idx)).collect();
+        assert_eq!(round_trip(env, &positions), positions);
+    });
+}
+
+#[test]
+fn list_from_any_list() {
+    with_env(|env| {
+        let first = vec3i!(1, 2, 3).to_jni(env).unwrap();
+        let second = vec3i!(4, 5, 6).to_jni(env).unwrap();
+        let list = env.call_static_method("java/util/List", "of", "(Ljava/lang/Object;Ljava/lang/Object;)Ljava/util/List;", &[
+            JValueGen::Object(&first),
+            JValueGen::Object(&second)
+        ]).unwrap().l().unwrap();
+
+        let read = Vec::<Vector3i>::from_jni(env, list);
+        assert!(read.is_ok(), "Failed to read an immutable list: {}", read.unwrap_err());
+        assert_eq!(read.unwrap(), vec![vec3i!(1, 2, 3), vec3i!(4, 5, 6)]);
+    });
+}
+
+#[test]
+fn invalid_objects() {
+    with_env(|env| {
+        let pos = vec3i!(0, 0, 0).to_jni(env).unwrap();
+        let kind = MoveKind::Traverse.to_jni(env).unwrap();
+        let negative = env.new_object(SpatialAction::<Vector3i>::CLASS,
+            "(Lcom/genericbadname/s4mc/math/Vector3i;Lcom/genericbadname/s4mc/pathing/MoveKind;JLcom/genericbadname/s4mc/math/Vector3i;)V", &[
+            JValueGen::Object(&pos),
+            JValueGen::Object(&kind),
+            JValueGen::Long(-1),
+            JValueGen::Object(&pos)
+        ]).unwrap();
+        assert!(SpatialAction::<Vector3i>::from_jni(env, negative).is_err(), "Accepted a negative move cost");
+
+        let place = env.get_static_field("com/genericbadname/s4mc/pathing/InteractionKind", "PLACE",
+            "Lcom/genericbadname/s4mc/pathing/InteractionKind;").unwrap().l().unwrap();
+        let faceless = env.new_object(Interaction::<Vector3i>::CLASS,
+            "(Lcom/genericbadname/s4mc/pathing/InteractionKind;Lcom/genericbadname/s4mc/math/Vector3i;Lcom/genericbadname/s4mc/math/Face;)V", &[
+            JValueGen::Object(&place),
+            JValueGen::Object(&pos),
+            JValueGen::Object(&JObject::null())
+        ]).unwrap();
+        assert!(Interaction::<Vector3i>::from_jni(env, faceless).is_err(), "Accepted a placement without a face");
+
+        let hitbox = Hitbox::PLAYER.to_jni(env).unwrap();
+        let sig = format!("({}{}IIZ)V", Hitbox::signature(), Tool::signature());
+        let in_debt = env.new_object(Agent::CLASS, sig, &[
+            JValueGen::Object(&hitbox),
+            JValueGen::Object(&JObject::null()),
+            JValueGen::Int(-1),
+            JValueGen::Int(300),
+            JValueGen::Bool(0)
+        ]).unwrap();
+        assert!(Agent::from_jni(env, in_debt).is_err(), "Accepted a negative amount of blocks");
+    });
+}
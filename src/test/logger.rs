@@ -0,0 +1,103 @@
+use crate::binding::logger::{self, level_filter, LOG_CLASS};
+use crate::test::jni::{jvm, with_env};
+use jni::objects::JValueGen;
+use jni::{JNIEnv, JavaVM};
+use log::{info, warn, LevelFilter};
+use std::sync::Once;
+use std::thread;
+
+static INSTALL: Once = Once::new();
+
+fn install() {
+    INSTALL.call_once(|| {
+        // SAFETY: the pointer comes from a JVM that lives for as long as the process
+        let vm = unsafe { JavaVM::from_raw(jvm().get_java_vm_pointer()) }.unwrap();
+        let installed = logger::install(vm);
+        assert!(installed.is_ok(), "Failed to install the logger: {}", installed.unwrap_err());
+    });
+}
+
+/// Returns whether Java received exactly `record`.
+fn received(env: &mut JNIEnv, record: &str) -> bool {
+    let records = env.get_static_field(LOG_CLASS, "RECORDS", "Ljava/util/List;").unwrap().l().unwrap();
+    let record = env.new_string(record).unwrap();
+    env.call_method(&records, "contains", "(Ljava/lang/Object;)Z", &[JValueGen::Object(&record)])
+        .unwrap().z().unwrap()
+}
+
+#[test]
+fn forwards_records() {
+    install();
+    with_env(|env| {
+        warn!(target: "s4rs::forwarded", "{} blocks left", 3);
+        assert!(received(env, "2 s4rs::forwarded: 3 blocks left"), "Record did not reach Java");
+
+        // other tests only log warnings, which this doesn't filter out
+        log::set_max_level(level_filter(2).unwrap());
+        info!(target: "s4rs::filtered", "too verbose");
+        log::set_max_level(logger::DEFAULT_MAX_LEVEL);
+        assert!(!received(env, "3 s4rs::filtered: too verbose"), "Record above the max level reached Java");
+    });
+}
+
+#[test]
+fn detached_threads_stay_detached() {
+    install();
+    thread::spawn(|| {
+        warn!(target: "s4rs::detached", "from nowhere");
+        assert!(jvm().get_env().is_err(), "Logging attached the thread");
+    }).join().unwrap();
+
+    // the record waits for a thread that can hand it over
+    with_env(|env| {
+        log::logger().flush();
+        assert!(received(env, "2 s4rs::detached: from nowhere"), "Record from a detached thread was lost");
+    });
+}
+
+#[test]
+fn deferred_records_keep_their_order() {
+    install();
+    thread::spawn(|| {
+        for idx in 0..3 {
+            warn!(target: "s4rs::ordered", "{}", idx);
+        }
+    }).join().unwrap();
+
+    with_env(|env| {
+        warn!(target: "s4rs::ordered", "attached");
+        let records = env.get_static_field(LOG_CLASS, "RECORDS", "Ljava/util/List;").unwrap().l().unwrap();
+        let position = |env: &mut JNIEnv, record: &str| {
+            let record = env.new_string(record).unwrap();
+            env.call_method(&records, "indexOf", "(Ljava/lang/Object;)I", &[JValueGen::Object(&record)])
+                .unwrap().i().unwrap()
+        };
+        let positions: Vec<_> = ["0", "1", "2", "attached"].iter()
+            .map(|message| position(env, &format!("2 s4rs::ordered: {}", message)))
+            .collect();
+        assert!(positions.iter().all(|position| *position >= 0), "Deferred records were lost");
+        assert!(positions.is_sorted(), "Deferred records were forwarded out of order: {:?}", positions);
+    });
+}
+
+#[test]
+fn pending_exception_left_alone() {
+    install();
+    with_env(|env| {
+        env.throw_new("java/lang/IllegalStateException", "pending").unwrap();
+        warn!(target: "s4rs::pending", "while throwing");
+        assert!(env.exception_check().unwrap(), "Logging swallowed a pending exception");
+        env.exception_clear().unwrap();
+        log::logger().flush();
+        assert!(received(env, "2 s4rs::pending: while throwing"), "Record logged while throwing was lost");
+    });
+}
+
+#[test]
+fn level_filters() {
+    assert_eq!(level_filter(0).ok(), Some(LevelFilter::Off));
+    assert_eq!(level_filter(1).ok(), Some(LevelFilter::Error));
+    assert_eq!(level_filter(5).ok(), Some(LevelFilter::Trace));
+    assert!(level_filter(6).is_err(), "Accepted a level past trace");
+    assert!(level_filter(-1).is_err(), "Accepted a negative level");
+}
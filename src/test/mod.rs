@@ -1,3 +1,15 @@
 mod config;
 mod data;
-mod algorithm;
\ No newline at end of file
+mod algorithm;
+mod world;
+mod block;
+mod goal;
+mod action;
+mod agent;
+mod handle;
+mod util;
+mod exception;
+mod pool;
+mod jni;
+mod cache;
+mod logger;
\ No newline at end of file
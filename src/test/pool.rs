@@ -0,0 +1,98 @@
+use crate::config::Configuration;
+use crate::pathing::agent::Agent;
+use crate::pathing::action::moveset_2d_cardinal;
+use crate::pathing::algorithm::PathCalculator;
+use crate::pathing::data::PathStatus;
+use crate::pathing::goal::GoalExact;
+use crate::pathing::math::Vector2i;
+use crate::pathing::pool::{PathPool, RequestStatus};
+use crate::test::algorithm::EndlessSpace;
+use crate::vec2i;
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+type EndlessCalculator = Arc<Mutex<PathCalculator<Vector2i, EndlessSpace>>>;
+
+fn calculator(timeout: Duration) -> EndlessCalculator {
+    let mut config = Configuration::new();
+    config.timeout = timeout;
+    let space = Box::new(EndlessSpace { wall: vec2i!(-1, -1), config });
+    Arc::new(Mutex::new(PathCalculator::new(moveset_2d_cardinal(), config, space)))
+}
+
+fn wait_until_done(pool: &PathPool<Vector2i, EndlessSpace>, id: u64) {
+    let start_time = Instant::now();
+    while pool.poll(id) == Some(RequestStatus::Pending) {
+        assert!(start_time.elapsed() < Duration::from_secs(10), "Request {} never finished", id);
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn submit_and_take() {
+    let pool = PathPool::new(2);
+    let id = pool.submit(calculator(Duration::from_secs(10)), vec2i!(0, 0), Box::new(GoalExact(vec2i!(3, 0))), Agent::new(), None);
+    assert!(id.is_ok(), "Failed to submit: {}", id.unwrap_err());
+    let id = id.unwrap();
+
+    wait_until_done(&pool, id);
+    assert_eq!(pool.poll(id), Some(RequestStatus::Done));
+    let result = pool.take(id);
+    assert!(result.as_ref().is_some_and(|r| r.as_ref().is_ok_and(|r| r.is_complete() && r.path.len() == 4)),
+            "Request did not complete with the shortest path");
+    assert_eq!(pool.poll(id), None, "Request was not forgotten once taken");
+    assert!(pool.take(id).is_none(), "Result was taken twice");
+}
+
+#[test]
+fn callback_receives_result() {
+    let pool = PathPool::new(1);
+    let (sender, receiver) = channel();
+    let id = pool.submit(calculator(Duration::from_secs(10)), vec2i!(0, 0), Box::new(GoalExact(vec2i!(0, 5))), Agent::new(),
+                         Some(Box::new(move |id, result| sender.send((id, result)).unwrap()))).unwrap();
+
+    let received = receiver.recv_timeout(Duration::from_secs(10));
+    assert!(received.is_ok(), "Callback was never called");
+    let (called_id, result) = received.unwrap();
+    assert_eq!(called_id, id);
+    assert!(result.is_ok_and(|r| r.is_complete()), "Callback did not receive the complete path");
+    assert_eq!(pool.poll(id), None, "Request with a callback was kept around");
+}
+
+#[test]
+fn cancel_running() {
+    let pool = PathPool::new(1);
+    // the goal is walled off, so only the timeout or a cancellation can end the search
+    let id = pool.submit(calculator(Duration::from_secs(60)), vec2i!(0, 0), Box::new(GoalExact(vec2i!(-1, -1))), Agent::new(), None).unwrap();
+    thread::sleep(Duration::from_millis(20));
+
+    let start_time = Instant::now();
+    assert!(pool.cancel(id), "Running request could not be cancelled");
+    wait_until_done(&pool, id);
+    assert!(start_time.elapsed() < Duration::from_secs(5), "Cancellation was not honored in time");
+    let result = pool.take(id).unwrap();
+    assert_eq!(result.map(|r| r.status).ok(), Some(PathStatus::Cancelled));
+}
+
+#[test]
+fn cancel_leaves_calculator_usable() {
+    let pool = PathPool::new(1);
+    let calc = calculator(Duration::from_secs(60));
+    let cancelled = pool.submit(Arc::clone(&calc), vec2i!(0, 0), Box::new(GoalExact(vec2i!(-1, -1))), Agent::new(), None).unwrap();
+    pool.cancel(cancelled);
+    let next = pool.submit(calc, vec2i!(0, 0), Box::new(GoalExact(vec2i!(2, 2))), Agent::new(), None).unwrap();
+
+    wait_until_done(&pool, next);
+    assert!(pool.take(next).unwrap().is_ok_and(|r| r.is_complete()), "Request after a cancelled one did not complete");
+    assert_eq!(pool.take(cancelled).unwrap().map(|r| r.status).ok(), Some(PathStatus::Cancelled));
+}
+
+#[test]
+fn unknown_request() {
+    let pool: PathPool<Vector2i, EndlessSpace> = PathPool::new(1);
+    assert_eq!(pool.poll(42), None);
+    assert!(pool.take(42).is_none());
+    assert!(!pool.cancel(42));
+}
@@ -0,0 +1,26 @@
+use crate::binding::util::{block_state, palette_indices};
+use crate::pathing::chunk::SECTION_VOLUME;
+
+#[test]
+fn palette_indices_widths() {
+    let bytes: Vec<u8> = (0..SECTION_VOLUME).map(|idx| (idx % 3) as u8).collect();
+    let narrow = palette_indices(&bytes);
+    assert!(narrow.is_ok(), "Failed to read byte indices: {}", narrow.unwrap_err());
+    assert_eq!(narrow.unwrap()[..4], [0, 1, 2, 0]);
+
+    let mut shorts = vec![0u8; SECTION_VOLUME * 2];
+    shorts[0..2].copy_from_slice(&300u16.to_be_bytes());
+    shorts[2..4].copy_from_slice(&1u16.to_be_bytes());
+    let wide = palette_indices(&shorts);
+    assert!(wide.is_ok(), "Failed to read short indices: {}", wide.unwrap_err());
+    assert_eq!(wide.unwrap()[..3], [300, 1, 0], "Short indices were not read as big-endian");
+
+    assert!(palette_indices(&[0; 100]).is_err(), "Read indices out of a buffer of the wrong size");
+}
+
+#[test]
+fn block_state_range() {
+    assert_eq!(block_state(12).ok(), Some(12));
+    assert!(block_state(-1).is_err(), "Accepted a negative block state");
+    assert!(block_state(70_000).is_err(), "Accepted a block state that doesn't fit");
+}
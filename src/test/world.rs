@@ -0,0 +1,432 @@
+use crate::binding::jni::JNICompatible;
+use crate::binding::pathing::{
+    Java_com_genericbadname_s4mc_pathing_PathCalculator_calculate, Java_com_genericbadname_s4mc_pathing_PathCalculator_create,
+    Java_com_genericbadname_s4mc_pathing_PathCalculator_free
+};
+use crate::binding::world::{
+    Java_com_genericbadname_s4mc_world_NativeWorld_create, Java_com_genericbadname_s4mc_world_NativeWorld_free,
+    Java_com_genericbadname_s4mc_world_NativeWorld_loadSection, Java_com_genericbadname_s4mc_world_NativeWorld_loadSectionInts,
+    Java_com_genericbadname_s4mc_world_NativeWorld_setBlock, Java_com_genericbadname_s4mc_world_NativeWorld_unloadChunk,
+    WORLDS
+};
+use crate::config::Configuration;
+use crate::pathing::action::default_moveset;
+use crate::pathing::algorithm::PathCalculator;
+use crate::pathing::block::{BlockClass, BlockInfo, BlockRegistry};
+use crate::pathing::chunk::{local_pos_of, section_pos_of, ChunkSection, AIR, SECTION_VOLUME};
+use crate::pathing::data::PathResult;
+use crate::pathing::math::Vector3i;
+use crate::pathing::world::{Space, VoxelSpace};
+use crate::test::jni::with_env;
+use crate::vec3i;
+use jni::objects::{JByteBuffer, JClass, JIntArray, JObject, JString, JValueGen};
+use jni::sys::{jlong, JNI_FALSE, JNI_TRUE};
+use jni::JNIEnv;
+use std::sync::Arc;
+
+const STONE: u16 = 1;
+
+fn registry() -> Arc<BlockRegistry> {
+    let mut registry = BlockRegistry::new();
+    registry.register(STONE, BlockInfo::new("minecraft:stone", BlockClass::Solid));
+    Arc::new(registry)
+}
+
+#[test]
+fn section_get_set() {
+    let mut section = ChunkSection::new();
+    assert_eq!(section.get(vec3i!(3, 4, 5)), AIR, "New section was not filled with air");
+
+    let previous = section.set(vec3i!(3, 4, 5), STONE);
+    assert_eq!(previous, AIR, "Setting a block did not return the previous state");
+    assert_eq!(section.get(vec3i!(3, 4, 5)), STONE, "Block was not set");
+    assert_eq!(section.get(vec3i!(5, 4, 3)), AIR, "Setting a block affected another position");
+}
+
+#[test]
+fn section_from_blocks() {
+    let mut blocks = vec![AIR; SECTION_VOLUME];
+    // YZX order
+    blocks[(2 << 8) | (1 << 4)] = STONE;
+    let section = ChunkSection::from_blocks(blocks);
+    assert!(section.is_ok(), "Failed to create a section: {}", section.unwrap_err());
+    assert_eq!(section.unwrap().get(vec3i!(0, 2, 1)), STONE, "Block array was not read in YZX order");
+
+    let too_small = ChunkSection::from_blocks(vec![AIR; 16]);
+    assert!(too_small.is_err(), "Created a section from an undersized block array");
+}
+
+#[test]
+fn section_coordinates() {
+    assert_eq!(section_pos_of(vec3i!(0, 0, 0)), vec3i!(0, 0, 0));
+    assert_eq!(section_pos_of(vec3i!(15, 16, 31)), vec3i!(0, 1, 1));
+    assert_eq!(section_pos_of(vec3i!(-1, -16, -17)), vec3i!(-1, -1, -2));
+    assert_eq!(local_pos_of(vec3i!(-1, -16, -17)), vec3i!(15, 0, 15));
+    assert_eq!(local_pos_of(vec3i!(17, 32, 5)), vec3i!(1, 0, 5));
+}
+
+#[test]
+fn load_unload_replace() {
+    let space = VoxelSpace::new(Configuration::new(), registry());
+    assert_eq!(space.get_block(vec3i!(0, 0, 0)), None, "Unloaded block returned a value");
+    assert!(space.set_block(vec3i!(0, 0, 0), STONE).is_err(), "Set a block in an unloaded section");
+    assert!(space.replace_section(vec3i!(0, 0, 0), ChunkSection::new()).is_err(),
+            "Replaced a section that was never loaded");
+
+    assert!(space.load_section(vec3i!(0, 0, 0), ChunkSection::new()).is_none());
+    assert!(space.load_section(vec3i!(0, 1, 0), ChunkSection::new()).is_none());
+    assert!(space.load_section(vec3i!(1, 0, 0), ChunkSection::new()).is_none());
+    assert_eq!(space.loaded_sections(), 3);
+
+    let set = space.set_block(vec3i!(4, 20, 4), STONE);
+    assert!(set.is_ok(), "Failed to set a block: {}", set.unwrap_err());
+    assert_eq!(space.get_block(vec3i!(4, 20, 4)), Some(STONE), "Block lookup did not match");
+
+    let replaced = space.replace_section(vec3i!(0, 1, 0), ChunkSection::filled(STONE));
+    assert!(replaced.is_ok(), "Failed to replace a section: {}", replaced.unwrap_err());
+    assert_eq!(replaced.unwrap().get(vec3i!(4, 4, 4)), STONE, "Replacing did not return the old section");
+    assert_eq!(space.get_block(vec3i!(0, 31, 0)), Some(STONE), "Section was not replaced");
+
+    assert_eq!(space.unload_chunk(0, 0), 2, "Did not unload every section in the chunk column");
+    assert!(!space.is_loaded(vec3i!(0, 0, 0)));
+    assert!(space.is_loaded(vec3i!(1, 0, 0)));
+    assert!(space.unload_section(vec3i!(1, 0, 0)).is_some());
+    assert_eq!(space.loaded_sections(), 0);
+}
+
+#[test]
+fn material_cost() {
+    let config = Configuration::new();
+    let space = VoxelSpace::new(config, registry());
+    space.load_section(vec3i!(0, 0, 0), ChunkSection::new());
+    let _ = space.set_block(vec3i!(1, 0, 0), STONE);
+
+    assert_eq!(space.material_cost(vec3i!(0, 0, 0)), 1.0, "Air was not passable");
+    assert_eq!(space.material_cost(vec3i!(1, 0, 0)), config.cost_inf, "Stone was passable");
+    assert_eq!(space.material_cost(vec3i!(-1, 0, 0)), config.cost_inf, "Unloaded space was passable");
+}
+
+#[test]
+fn pathfinder_voxel_wall() {
+    let config = Configuration::new();
+    let space = VoxelSpace::new(config, registry());
+    space.load_section(vec3i!(0, 0, 0), ChunkSection::new());
+    for x in 0..16 {
+        for z in 0..16 {
+            let _ = space.set_block(vec3i!(x, 0, z), STONE);
+        }
+    }
+    // two block tall wall along x = 2, with a gap at z = 5
+    for z in 0..16 {
+        if z != 5 {
+            let _ = space.set_block(vec3i!(2, 1, z), STONE);
+            let _ = space.set_block(vec3i!(2, 2, z), STONE);
+        }
+    }
+
+    let mut calc = PathCalculator::new(default_moveset(), config, Box::new(space));
+    let out = calc.calculate(vec3i!(0, 1, 0), vec3i!(4, 1, 0));
+    assert!(out.is_ok(), "Pathfinding failed with error: {}", out.unwrap_err());
+    let result = out.unwrap();
+    assert!(result.is_complete(), "Pathfinder did not complete the path");
+    assert_eq!(result.end(), Some(vec3i!(4, 1, 0)), "Pathfinder did not reach the end");
+    let path = result.path;
+    assert!(path.iter().any(|n| n.action.pos == vec3i!(2, 1, 5)), "Pathfinder did not go through the gap");
+    assert_eq!(path.len(), 13, "Pathfinder did not choose the optimal path");
+}
+
+#[test]
+fn shared_space() {
+    // calculators on a shared space see changes made to it after they were created
+    let config = Configuration::new();
+    let space = Arc::new(VoxelSpace::new(config, registry()));
+    space.load_section(vec3i!(0, 0, 0), ChunkSection::new());
+    let mut first = PathCalculator::new(default_moveset(), config, Box::new(Arc::clone(&space)));
+    let mut second = PathCalculator::new(default_moveset(), config, Box::new(Arc::clone(&space)));
+
+    for x in 0..4 {
+        let _ = space.set_block(vec3i!(x, 0, 0), STONE);
+    }
+    for calc in [&mut first, &mut second] {
+        let out = calc.calculate(vec3i!(0, 1, 0), vec3i!(3, 1, 0));
+        assert!(out.is_ok_and(|result| result.is_complete()), "Calculator did not see the shared space");
+    }
+}
+
+#[test]
+fn section_from_palette() {
+    // the palette has an unused entry, which shouldn't make it into the section
+    let palette = [AIR, STONE, 7];
+    let indices: Vec<u16> = (0..SECTION_VOLUME).map(|idx| (idx % 2) as u16).collect();
+    let section = ChunkSection::from_palette(&palette, &indices);
+    assert!(section.is_ok(), "Failed to create a section: {}", section.unwrap_err());
+    let section = section.unwrap();
+    assert_eq!(section.get(vec3i!(0, 0, 0)), AIR);
+    assert_eq!(section.get(vec3i!(1, 0, 0)), STONE, "Indices were not resolved through the palette");
+    assert_eq!(section.palette_len(), Some(2), "Unused palette entry was kept");
+
+    let out_of_bounds = ChunkSection::from_palette(&palette, &vec![3; SECTION_VOLUME]);
+    assert!(out_of_bounds.is_err(), "Accepted an index past the end of the palette");
+    let too_small = ChunkSection::from_palette(&palette, &[0; 16]);
+    assert!(too_small.is_err(), "Created a section from too few indices");
+}
+
+#[test]
+fn section_single_value() {
+    let section = ChunkSection::filled(STONE);
+    assert_eq!(section.bits_per_entry(), 0, "Single-value section stored indices");
+    assert_eq!(section.palette_len(), Some(1));
+
+    let uniform = ChunkSection::from_blocks(vec![STONE; SECTION_VOLUME]).unwrap();
+    assert_eq!(uniform.bits_per_entry(), 0, "Uniform block array was not collapsed");
+    assert_eq!(uniform, section, "Uniform sections were not equal");
+}
+
+#[test]
+fn section_palette_growth() {
+    let mut section = ChunkSection::new();
+    section.set(vec3i!(0, 0, 0), STONE);
+    assert_eq!(section.bits_per_entry(), 4, "Palette did not start at the minimum width");
+
+    // 17 states no longer fit into 4 bits
+    for state in 2..=17u16 {
+        section.set(vec3i!(state as i32, 1, 0), state);
+    }
+    assert_eq!(section.palette_len(), Some(18));
+    assert_eq!(section.bits_per_entry(), 5, "Palette was not widened");
+    assert_eq!(section.get(vec3i!(0, 0, 0)), STONE, "Widening lost a block");
+    for state in 2..=17u16 {
+        assert_eq!(section.get(vec3i!(state as i32, 1, 0)), state, "Widening lost a block");
+    }
+
+    // past 8 bits, states are stored directly
+    for (idx, state) in (18..=300u16).enumerate() {
+        let idx = idx as i32;
+        section.set(vec3i!(idx % 16, 2 + idx / 256, (idx / 16) % 16), state);
+    }
+    assert_eq!(section.palette_len(), None, "Oversized palette was not dropped");
+    assert_eq!(section.bits_per_entry(), 16);
+    assert_eq!(section.get(vec3i!(0, 2, 0)), 18, "Switching to direct storage lost a block");
+    assert_eq!(section.get(vec3i!(5, 1, 0)), 5, "Switching to direct storage lost a block");
+}
+
+#[test]
+fn section_compact() {
+    let mut section = ChunkSection::new();
+    for x in 0..16 {
+        section.set(vec3i!(x, 0, 0), x as u16 + 1);
+    }
+    for x in 0..16 {
+        section.set(vec3i!(x, 0, 0), AIR);
+    }
+    assert_eq!(section.palette_len(), Some(17), "Palette shrank before compacting");
+
+    section.compact();
+    assert_eq!(section.palette_len(), Some(1), "Compacting did not drop unused states");
+    assert_eq!(section.bits_per_entry(), 0, "Compacting did not collapse the section");
+    assert_eq!(section, ChunkSection::new());
+}
+
+#[test]
+fn section_round_trip() {
+    let blocks: Vec<u16> = (0..SECTION_VOLUME).map(|idx| (idx % 7) as u16).collect();
+    let section = ChunkSection::from_blocks(blocks.clone()).unwrap();
+    assert_eq!(section.bits_per_entry(), 4);
+    assert_eq!(section.unpack(), blocks, "Packed section did not unpack to the same blocks");
+}
+
+#[test]
+fn memory_usage() {
+    let space = VoxelSpace::new(Configuration::new(), registry());
+    for y in 0..16 {
+        space.load_section(vec3i!(0, y, 0), ChunkSection::filled(STONE));
+    }
+    let uniform = space.memory_usage();
+
+    let mut mixed = ChunkSection::new();
+    mixed.set(vec3i!(1, 1, 1), STONE);
+    space.load_section(vec3i!(1, 0, 0), mixed);
+    let with_mixed = space.memory_usage();
+
+    // no indices are stored, only the sections themselves
+    assert!(uniform < 16 * 128, "Uniform sections used {} bytes", uniform);
+    // 4096 4-bit indices, packed into 256 words
+    assert!(with_mixed - uniform >= 2048, "Mixed section was not accounted for");
+    assert!(with_mixed - uniform < SECTION_VOLUME * 2, "Mixed section was not compressed");
+}
+
+/// Block table handed to worlds created through the entry points.
+const JAVA_BLOCKS: &str = r#"[
+    { "id": 1, "name": "minecraft:stone", "class": "solid" },
+    { "id": 4, "name": "minecraft:dirt", "class": "solid", "hardness": 0.5, "tool": "shovel" }
+]"#;
+
+/// Returns a second `JNIEnv` to call an entry point with, the way Java would.
+fn java_env<'local>(env: &JNIEnv<'local>) -> JNIEnv<'local> {
+    // SAFETY: the clone is only used on this thread, for the length of a single call
+    unsafe { env.unsafe_clone() }
+}
+
+/// Stands in for the class an entry point is called on, which none of them use.
+fn java_class<'local>() -> JClass<'local> {
+    JClass::from(JObject::null())
+}
+
+/// Creates a world through `NativeWorld.create`, with `config` as its configuration JSON.
+fn create_world(env: &mut JNIEnv, config: Option<&str>) -> jlong {
+    let blocks = env.new_string(JAVA_BLOCKS).unwrap();
+    let config = match config {
+        Some(config) => env.new_string(config).unwrap(),
+        None => JString::from(JObject::null())
+    };
+    let handle = Java_com_genericbadname_s4mc_world_NativeWorld_create(java_env(env), java_class(), blocks, config);
+    assert!(!env.exception_check().unwrap(), "Creating a world threw");
+    handle
+}
+
+#[test]
+fn config_from_java() {
+    with_env(|env| {
+        let breaking = create_world(env, Some(r#"{ "allow_breaking": true }"#));
+        let default = create_world(env, None);
+
+        // a floor of stone, with a dirt wall two blocks tall across the whole section
+        let palette = env.new_int_array(3).unwrap();
+        env.set_int_array_region(&palette, 0, &[AIR as i32, 1, 4]).unwrap();
+        let indices: Vec<i32> = (0..SECTION_VOLUME as i32)
+            .map(|idx| match (idx >> 8, idx & 15) {
+                (0, _) => 1,
+                (1 | 2, 2) => 2,
+                _ => 0
+            })
+            .collect();
+        let indices_array = env.new_int_array(SECTION_VOLUME as i32).unwrap();
+        env.set_int_array_region(&indices_array, 0, &indices).unwrap();
+
+        for (world, breaks) in [(breaking, true), (default, false)] {
+            Java_com_genericbadname_s4mc_world_NativeWorld_loadSectionInts(
+                java_env(env), java_class(), world, 0, 0, 0,
+                env.new_local_ref(&palette).unwrap().into(), env.new_local_ref(&indices_array).unwrap().into()
+            );
+            let calc = Java_com_genericbadname_s4mc_pathing_PathCalculator_create(java_env(env), java_class(), world);
+            assert_ne!(calc, 0, "Failed to create a calculator");
+
+            let start = vec3i!(0, 1, 0).to_jni(env).unwrap();
+            let end = vec3i!(4, 1, 0).to_jni(env).unwrap();
+            let raw = Java_com_genericbadname_s4mc_pathing_PathCalculator_calculate(
+                java_env(env), java_class(), calc, start, end, JObject::null()
+            );
+            assert!(!env.exception_check().unwrap(), "Calculating threw");
+            // SAFETY: the entry point returns a local reference to a `PathResult`
+            let result = PathResult::<Vector3i>::from_jni(env, unsafe { JObject::from_raw(raw) }).unwrap();
+            assert_eq!(result.is_complete(), breaks, "Calculator did not use the world's configuration");
+
+            Java_com_genericbadname_s4mc_pathing_PathCalculator_free(java_env(env), java_class(), calc);
+            Java_com_genericbadname_s4mc_world_NativeWorld_free(java_env(env), java_class(), world);
+        }
+    });
+}
+
+/// Returns whether the last entry point threw, clearing the exception.
+fn threw(env: &mut JNIEnv) -> bool {
+    let thrown = env.exception_check().unwrap();
+    if thrown {
+        env.exception_clear().unwrap();
+    }
+    thrown
+}
+
+/// Creates an `int[]` holding `values`.
+fn int_array<'local>(env: &mut JNIEnv<'local>, values: &[i32]) -> JIntArray<'local> {
+    let array = env.new_int_array(values.len() as i32).unwrap();
+    env.set_int_array_region(&array, 0, values).unwrap();
+    array
+}
+
+/// Wraps `bytes` in a direct `ByteBuffer`. The buffer must not outlive `bytes`.
+fn direct_buffer<'local>(env: &mut JNIEnv<'local>, bytes: &mut [u8]) -> JByteBuffer<'local> {
+    // SAFETY: every test keeps `bytes` alive until it's done with the buffer
+    unsafe { env.new_direct_byte_buffer(bytes.as_mut_ptr(), bytes.len()) }.unwrap()
+}
+
+/// Calls `NativeWorld.loadSection` for the section at `0, 0, 0`, with a palette of air and stone.
+fn load_section(env: &mut JNIEnv, world: jlong, indices: JByteBuffer) {
+    let palette = int_array(env, &[AIR as i32, STONE as i32]);
+    Java_com_genericbadname_s4mc_world_NativeWorld_loadSection(java_env(env), java_class(), world, 0, 0, 0, palette, indices);
+}
+
+#[test]
+fn load_section_from_java() {
+    with_env(|env| {
+        let world = create_world(env, None);
+        let space = WORLDS.get(world).unwrap();
+
+        let mut bytes: Vec<u8> = (0..SECTION_VOLUME).map(|idx| (idx % 2) as u8).collect();
+        let indices = direct_buffer(env, &mut bytes);
+        load_section(env, world, indices);
+        assert!(!threw(env), "Loading a section threw");
+        assert_eq!(space.get_block(vec3i!(0, 0, 0)), Some(AIR));
+        assert_eq!(space.get_block(vec3i!(1, 0, 0)), Some(STONE), "Section was not loaded from the buffer");
+
+        // only the indices up to the limit are read, however big the buffer
+        let mut shorts = vec![0u8; SECTION_VOLUME * 2];
+        shorts[SECTION_VOLUME..].fill(1);
+        let indices = direct_buffer(env, &mut shorts);
+        env.call_method(&indices, "limit", "(I)Ljava/nio/Buffer;", &[JValueGen::Int(SECTION_VOLUME as i32)]).unwrap();
+        load_section(env, world, indices);
+        assert!(!threw(env), "Loading a section from a limited buffer threw");
+        assert_eq!(space.get_block(vec3i!(1, 0, 0)), Some(AIR), "Indices were read as shorts past the limit");
+
+        let mut short = vec![1u8; 100];
+        let indices = direct_buffer(env, &mut short);
+        load_section(env, world, indices);
+        assert!(threw(env), "Loaded a section out of too few indices");
+
+        let mut moved = vec![1u8; SECTION_VOLUME];
+        let indices = direct_buffer(env, &mut moved);
+        env.call_method(&indices, "position", "(I)Ljava/nio/Buffer;", &[JValueGen::Int(1)]).unwrap();
+        load_section(env, world, indices);
+        assert!(threw(env), "Loaded a section from a buffer that wasn't at position 0");
+        assert_eq!(space.get_block(vec3i!(1, 0, 0)), Some(AIR), "Rejected buffer replaced the section");
+
+        Java_com_genericbadname_s4mc_world_NativeWorld_free(java_env(env), java_class(), world);
+    });
+}
+
+#[test]
+fn edit_world_from_java() {
+    with_env(|env| {
+        let world = create_world(env, None);
+        let space = WORLDS.get(world).unwrap();
+
+        let set_block = |env: &mut JNIEnv, pos: Vector3i, state: i32| {
+            Java_com_genericbadname_s4mc_world_NativeWorld_setBlock(java_env(env), java_class(), world, pos.x, pos.y, pos.z, state)
+        };
+        assert_eq!(set_block(env, vec3i!(1, 2, 3), STONE as i32), JNI_FALSE, "Set a block in an unloaded section");
+
+        let palette = int_array(env, &[AIR as i32]);
+        let indices = int_array(env, &[0; SECTION_VOLUME]);
+        Java_com_genericbadname_s4mc_world_NativeWorld_loadSectionInts(java_env(env), java_class(), world, 0, 0, 0, palette, indices);
+        let palette = int_array(env, &[STONE as i32]);
+        let indices = int_array(env, &[0; SECTION_VOLUME]);
+        Java_com_genericbadname_s4mc_world_NativeWorld_loadSectionInts(java_env(env), java_class(), world, 0, 1, 0, palette, indices);
+        assert!(!threw(env), "Loading a section from an int[] threw");
+        assert_eq!(space.get_block(vec3i!(0, 16, 0)), Some(STONE), "Section was not loaded from the int[]");
+
+        let palette = int_array(env, &[AIR as i32]);
+        let indices = int_array(env, &[1; SECTION_VOLUME]);
+        Java_com_genericbadname_s4mc_world_NativeWorld_loadSectionInts(java_env(env), java_class(), world, 1, 0, 0, palette, indices);
+        assert!(threw(env), "Loaded a section with indices outside its palette");
+
+        assert_eq!(set_block(env, vec3i!(1, 2, 3), STONE as i32), JNI_TRUE, "Did not set a block in a loaded section");
+        assert_eq!(space.get_block(vec3i!(1, 2, 3)), Some(STONE));
+        assert_eq!(set_block(env, vec3i!(1, 2, 3), -1), JNI_FALSE);
+        assert!(threw(env), "Set a negative block state");
+
+        let unloaded = Java_com_genericbadname_s4mc_world_NativeWorld_unloadChunk(java_env(env), java_class(), world, 0, 0);
+        assert_eq!(unloaded, 2, "Did not unload every section of the chunk");
+        assert!(!space.is_loaded(vec3i!(0, 0, 0)) && !space.is_loaded(vec3i!(0, 1, 0)));
+
+        Java_com_genericbadname_s4mc_world_NativeWorld_free(java_env(env), java_class(), world);
+    });
+}